@@ -0,0 +1,254 @@
+use crate::engine::Input;
+use ggez::event::{Button, KeyCode};
+use serde::{Deserialize, Serialize};
+
+/// Everything a player can do with a key or button while playing
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Action {
+    MoveLeft,
+    MoveRight,
+    SoftDrop,
+    HardDrop,
+    RotateCW,
+    RotateCCW,
+    Rotate180,
+    Hold,
+    Pause,
+}
+
+impl Action {
+    pub fn all() -> Vec<Action> {
+        use Action::*;
+        vec![
+            MoveLeft, MoveRight, SoftDrop, HardDrop, RotateCW, RotateCCW, Rotate180, Hold, Pause,
+        ]
+    }
+
+    pub fn name(self) -> &'static str {
+        use Action::*;
+        match self {
+            MoveLeft => "Move Left",
+            MoveRight => "Move Right",
+            SoftDrop => "Soft Drop",
+            HardDrop => "Hard Drop",
+            RotateCW => "Rotate CW",
+            RotateCCW => "Rotate CCW",
+            Rotate180 => "Rotate 180",
+            Hold => "Hold",
+            Pause => "Pause",
+        }
+    }
+
+    /// The engine input of this action, None if the scene handles it itself
+    pub fn input(self) -> Option<Input> {
+        use Action::*;
+        match self {
+            MoveLeft => Some(Input::MoveLeft),
+            MoveRight => Some(Input::MoveRight),
+            SoftDrop => Some(Input::SoftDrop),
+            HardDrop => Some(Input::HardDrop),
+            RotateCW => Some(Input::RotateCW),
+            RotateCCW => Some(Input::RotateCCW),
+            Rotate180 => Some(Input::Rotate180),
+            Hold => Some(Input::Hold),
+            Pause => None,
+        }
+    }
+}
+
+/// A physical key or gamepad button
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Trigger {
+    Key(KeyCode),
+    Button(Button),
+}
+
+impl Trigger {
+    /// Menus are always navigated with the same keys so they can't be unbound
+    pub fn menu_key(self) -> Option<KeyCode> {
+        use Button::*;
+        match self {
+            Trigger::Key(key) => Some(key),
+            Trigger::Button(btn) => match btn {
+                DPadDown => Some(KeyCode::Down),
+                DPadUp => Some(KeyCode::Up),
+                DPadLeft => Some(KeyCode::Left),
+                DPadRight => Some(KeyCode::Right),
+                South => Some(KeyCode::Return),
+                East | Start | Select => Some(KeyCode::Escape),
+                _ => None,
+            },
+        }
+    }
+
+    pub fn name(self) -> String {
+        match self {
+            Trigger::Key(key) => format!("{:?}", key),
+            Trigger::Button(btn) => format!("Pad {:?}", btn),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Binding {
+    pub action: Action,
+    pub keys: Vec<KeyCode>,
+    pub buttons: Vec<Button>,
+}
+
+impl Binding {
+    fn new(action: Action, keys: Vec<KeyCode>, buttons: Vec<Button>) -> Binding {
+        Binding {
+            action,
+            keys,
+            buttons,
+        }
+    }
+
+    pub fn triggers(&self) -> Vec<Trigger> {
+        let keys = self.keys.iter().map(|k| Trigger::Key(*k));
+        let buttons = self.buttons.iter().map(|b| Trigger::Button(*b));
+        keys.chain(buttons).collect()
+    }
+}
+
+/// Maps keys and gamepad buttons to actions, one binding per action
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default = "Controls::default")]
+pub struct Controls {
+    bindings: Vec<Binding>,
+}
+
+impl Controls {
+    pub fn default() -> Controls {
+        use Action::*;
+        use Button::*;
+        use KeyCode::*;
+        Controls {
+            bindings: vec![
+                Binding::new(MoveLeft, vec![Left], vec![DPadLeft]),
+                Binding::new(MoveRight, vec![Right], vec![DPadRight]),
+                Binding::new(SoftDrop, vec![Down], vec![DPadDown]),
+                Binding::new(HardDrop, vec![Up], vec![DPadUp]),
+                Binding::new(RotateCW, vec![Tab], vec![East]),
+                Binding::new(RotateCCW, vec![Space, Return], vec![South]),
+                Binding::new(Rotate180, vec![A], vec![]),
+                Binding::new(Hold, vec![Q, J], vec![LeftTrigger, RightTrigger]),
+                Binding::new(Action::Pause, vec![P, Escape], vec![Start, Select]),
+            ],
+        }
+    }
+
+    /// Gives actions missing from a loaded file their default binding
+    /// and Pause its default triggers if it has none, so a game can always be paused
+    pub fn validated(mut self) -> Controls {
+        for default in Controls::default().bindings {
+            match self.binding(default.action) {
+                Some(binding) if default.action == Action::Pause => {
+                    if binding.triggers().is_empty() {
+                        for trigger in default.triggers() {
+                            self.bind(Action::Pause, trigger);
+                        }
+                    }
+                }
+                Some(_) => {}
+                None => self.bindings.push(default),
+            }
+        }
+        self
+    }
+
+    pub fn action(&self, trigger: Trigger) -> Option<Action> {
+        self.bindings
+            .iter()
+            .find(|b| b.triggers().contains(&trigger))
+            .map(|b| b.action)
+    }
+
+    pub fn binding(&self, action: Action) -> Option<&Binding> {
+        self.bindings.iter().find(|b| b.action == action)
+    }
+
+    /// Binds the trigger to the action and removes it from every other action
+    pub fn bind(&mut self, action: Action, trigger: Trigger) {
+        for binding in self.bindings.iter_mut() {
+            match trigger {
+                Trigger::Key(key) => binding.keys.retain(|k| *k != key),
+                Trigger::Button(btn) => binding.buttons.retain(|b| *b != btn),
+            }
+        }
+        if let Some(binding) = self.bindings.iter_mut().find(|b| b.action == action) {
+            match trigger {
+                Trigger::Key(key) => binding.keys.push(key),
+                Trigger::Button(btn) => binding.buttons.push(btn),
+            }
+        }
+    }
+
+    /// Removes every trigger of the action, except from Pause which must stay reachable
+    pub fn clear(&mut self, action: Action) {
+        if action == Action::Pause {
+            return;
+        }
+        if let Some(binding) = self.bindings.iter_mut().find(|b| b.action == action) {
+            binding.keys.clear();
+            binding.buttons.clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn triggers(controls: &Controls, action: Action) -> Vec<Trigger> {
+        controls.binding(action).unwrap().triggers()
+    }
+
+    #[test]
+    fn bind_takes_the_trigger_from_other_actions() {
+        let mut controls = Controls::default();
+        let tab = Trigger::Key(KeyCode::Tab);
+        controls.bind(Action::Hold, tab);
+        assert_eq!(controls.action(tab), Some(Action::Hold));
+        assert!(!triggers(&controls, Action::RotateCW).contains(&tab));
+        assert!(triggers(&controls, Action::Hold).contains(&tab));
+    }
+
+    #[test]
+    fn clear_keeps_pause() {
+        let mut controls = Controls::default();
+        controls.clear(Action::Hold);
+        assert!(triggers(&controls, Action::Hold).is_empty());
+        assert_eq!(controls.action(Trigger::Key(KeyCode::Q)), None);
+        controls.clear(Action::Pause);
+        assert_eq!(
+            triggers(&controls, Action::Pause),
+            triggers(&Controls::default(), Action::Pause)
+        );
+    }
+
+    #[test]
+    fn validated_restores_missing_actions_and_pause() {
+        let mut controls = Controls::default();
+        controls.bindings.retain(|b| b.action != Action::Hold);
+        for trigger in triggers(&controls, Action::Pause) {
+            controls.bind(Action::RotateCW, trigger);
+        }
+        assert!(triggers(&controls, Action::Pause).is_empty());
+
+        let controls = controls.validated();
+        assert_eq!(
+            triggers(&controls, Action::Hold),
+            triggers(&Controls::default(), Action::Hold)
+        );
+        assert_eq!(
+            triggers(&controls, Action::Pause),
+            triggers(&Controls::default(), Action::Pause)
+        );
+        assert_eq!(
+            controls.action(Trigger::Key(KeyCode::Escape)),
+            Some(Action::Pause)
+        );
+    }
+}
@@ -0,0 +1,91 @@
+use super::scoring::{LineClear, Spin};
+use serde::{Deserialize, Serialize};
+
+/// How many garbage rows a clear sends to the opponent in versus mode
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AttackTable {
+    /// Indexed by lines cleared
+    pub lines: [usize; 5],
+    /// Indexed by lines cleared with a full T-spin
+    pub tspin: [usize; 4],
+    /// Indexed by lines cleared with a T-spin mini
+    pub tspin_mini: [usize; 3],
+    /// Bonus for the n-th consecutive clear, the last entry repeats
+    pub combo: Vec<usize>,
+    pub back_to_back: usize,
+    /// Sent instead of everything else when the field is left empty
+    pub perfect_clear: usize,
+}
+
+impl AttackTable {
+    pub fn guideline() -> AttackTable {
+        AttackTable {
+            lines: [0, 0, 1, 2, 4],
+            tspin: [0, 2, 4, 6],
+            tspin_mini: [0, 0, 1],
+            combo: vec![0, 0, 1, 1, 1, 2, 2, 3, 3, 4, 4, 4, 5],
+            back_to_back: 1,
+            perfect_clear: 10,
+        }
+    }
+
+    /// Returns the garbage rows sent for a clear
+    pub fn attack(&self, clear: &LineClear) -> usize {
+        if clear.lines == 0 {
+            return 0;
+        }
+        if clear.perfect_clear && self.perfect_clear > 0 {
+            return self.perfect_clear;
+        }
+        let mut attack = match clear.spin {
+            Spin::None => self.lines[clear.lines.min(self.lines.len() - 1)],
+            Spin::Mini => self.tspin_mini[clear.lines.min(self.tspin_mini.len() - 1)],
+            Spin::Full => self.tspin[clear.lines.min(self.tspin.len() - 1)],
+        };
+        if clear.back_to_back {
+            attack += self.back_to_back;
+        }
+        if let Some(last) = self.combo.last() {
+            attack += *self.combo.get(clear.combo).unwrap_or(last);
+        }
+        attack
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clear(lines: usize, spin: Spin) -> LineClear {
+        LineClear {
+            lines,
+            spin,
+            combo: 0,
+            back_to_back: false,
+            perfect_clear: false,
+        }
+    }
+
+    #[test]
+    fn guideline_values() {
+        let table = AttackTable::guideline();
+        let tetris = clear(4, Spin::None);
+        let tsd = clear(2, Spin::Full);
+        #[rustfmt::skip]
+        let attacks = [
+            (clear(1, Spin::None), 0),
+            (clear(2, Spin::None), 1),
+            (tetris, 4),
+            (tsd, 4),
+            (clear(0, Spin::Full), 0),
+            (LineClear { back_to_back: true, ..tetris }, 5),
+            (LineClear { back_to_back: true, ..tsd }, 5),
+            (LineClear { combo: 2, ..clear(1, Spin::None) }, 1),
+            (LineClear { perfect_clear: true, ..clear(1, Spin::None) }, 10),
+            (LineClear { perfect_clear: true, back_to_back: true, ..tetris }, 10),
+        ];
+        for (clear, attack) in attacks.iter() {
+            assert_eq!(table.attack(clear), *attack, "{:?}", clear);
+        }
+    }
+}
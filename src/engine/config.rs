@@ -0,0 +1,54 @@
+use super::attack::AttackTable;
+use super::level::GameMode;
+use super::position::Position;
+use super::randomizer::Randomizer;
+use crate::field::BoardSize;
+use crate::piece::{PieceSet, RotationSystem};
+use crate::util::LockDown;
+use rand::{thread_rng, Rng};
+use serde::{Deserialize, Serialize};
+
+/// Everything that decides how a single game plays out, chosen before it starts.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EngineConfig {
+    pub mode: GameMode,
+    pub randomizer: Randomizer,
+    pub piece_set: PieceSet,
+    pub rotation_system: RotationSystem,
+    pub lock_down: LockDown,
+    pub attack_table: AttackTable,
+    /// Size of the field, unless `position` brings its own
+    pub board: BoardSize,
+    /// Chance that the hole moves to another column between two rows of dig garbage
+    pub dig_messiness: f64,
+    /// Board and pieces to start from instead of an empty field
+    pub position: Option<Position>,
+    /// Seeds both the piece sequence and the garbage holes
+    pub seed: u64,
+}
+
+impl EngineConfig {
+    /// Returns the default config with a fresh random seed
+    pub fn new() -> EngineConfig {
+        EngineConfig {
+            mode: GameMode::Marathon,
+            randomizer: Randomizer::Bag7,
+            piece_set: PieceSet::standard(),
+            rotation_system: RotationSystem::Srs,
+            lock_down: LockDown::MoveReset,
+            attack_table: AttackTable::guideline(),
+            board: BoardSize::default(),
+            dig_messiness: 0.3,
+            position: None,
+            seed: thread_rng().gen(),
+        }
+    }
+
+    /// Returns the same config with a fresh random seed
+    pub fn reseeded(&self) -> EngineConfig {
+        EngineConfig {
+            seed: thread_rng().gen(),
+            ..self.clone()
+        }
+    }
+}
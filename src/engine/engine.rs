@@ -0,0 +1,873 @@
+use super::config::EngineConfig;
+use super::input::Input;
+use super::level::*;
+use super::randomizer::FixedGenerator;
+use super::replay::ReplayEvent;
+use super::scoring::{LineClear, Spin};
+use crate::block::*;
+use crate::field::*;
+use crate::piece::*;
+use crate::util::{types::*, LockDelay, RngPieceQueue};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+/// Length of one simulation step in seconds
+pub const TICK_DELTA: f64 = 1. / 60.;
+/// When cleared rows disappear, at the latest half way through the line clear delay
+const DESTROY_HIDE_DELTA: f64 = 0.12;
+/// Index of the SRS kick that turns a T-spin mini into a full T-spin
+const TST_KICK: usize = 4;
+
+/// Things that happened inside the engine that the outside might care about
+/// (sounds, network, callouts). Collected with `Engine::take_events`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum EngineEvent {
+    PieceLocked,
+    /// A piece locked and either cleared lines or was a spin
+    LinesCleared(LineClear),
+    /// Garbage rows to send after cancelling against incoming garbage
+    Attack(usize),
+    GameOver,
+    /// The goal of the mode was reached
+    Finished,
+}
+
+/// The rules of the game without any rendering. It is driven by `input` and
+/// advanced in fixed steps of `TICK_DELTA`, so it runs the same with or without a window.
+#[derive(Clone, Debug)]
+pub struct Engine {
+    config: EngineConfig,
+    delta: f64, // time not yet consumed by a tick
+    gravity_delta: f64,
+    ticks: u64,
+    leveling: LevelingType, // level and time to next
+    field: PlayingField,
+    piece: PieceState,
+    next_pieces: RngPieceQueue,
+    hold_piece: Option<HoldPiece>,
+    /// Set when the last successful action was a rotation: its direction and the kick used
+    last_rotation: Option<(RDir, usize)>,
+    /// Consecutive locks that cleared lines, None after one that didn't
+    combo: Option<usize>,
+    /// Whether the last clear was difficult, for back-to-back
+    back_to_back: bool,
+    /// Set while there is a falling piece
+    lock_delay: Option<LockDelay>,
+    destroying_rows_indices: Option<(f64, Vec<usize>)>,
+    garbage_to_add: usize,
+    garbage_rng: StdRng,
+    /// Column of the hole in the last row of dig garbage
+    dig_hole: usize,
+    /// Set when the game has ended, by topping out or by finishing
+    game_over: bool,
+    finished: bool,
+    /// What `time` was when the game ended
+    end_time: Option<f64>,
+    pieces: usize,
+    events: Vec<EngineEvent>,
+    /// Everything put into the engine with the tick it happened before, for replays
+    recording: Vec<(u64, ReplayEvent)>,
+}
+
+impl Engine {
+    pub fn new(leveling: LevelingType, config: EngineConfig) -> Engine {
+        // offset so the holes don't follow the piece sequence
+        let mut garbage_rng = StdRng::seed_from_u64(config.seed.wrapping_add(1));
+        let mut generator = config
+            .randomizer
+            .generator(config.seed, config.piece_set.shapes.clone());
+        let mut field = PlayingField::with_size(config.board);
+        let mut hold_piece = None;
+        if let Some(position) = &config.position {
+            generator = Box::new(FixedGenerator::new(position.queue.clone(), generator));
+            field = position.field.clone();
+            hold_piece = position
+                .hold
+                .clone()
+                .map(|shape| HoldPiece::Unlocked(Piece::new(shape)));
+        }
+        let dig_hole = garbage_rng.gen_range(0, field.width());
+        let mut engine = Engine {
+            delta: 0.,
+            gravity_delta: 0.,
+            ticks: 0,
+            leveling,
+            field,
+            piece: NoPiece(0., 0.),
+            next_pieces: RngPieceQueue::new(generator),
+            hold_piece,
+            last_rotation: None,
+            combo: None,
+            back_to_back: false,
+            lock_delay: None,
+            destroying_rows_indices: None,
+            garbage_to_add: 0,
+            garbage_rng,
+            dig_hole,
+            game_over: false,
+            finished: false,
+            end_time: None,
+            pieces: 0,
+            events: vec![],
+            recording: vec![],
+            config,
+        };
+        engine.refill_dig();
+        engine
+    }
+
+    /// Advances the engine by as many ticks as fit into the elapsed time
+    pub fn update(&mut self, dt: f64) {
+        if self.game_over {
+            return;
+        }
+        let mut delta = self.delta + dt;
+        // a game that ends during a tick ends at that tick, not at the end of the frame
+        self.delta = 0.;
+        while delta >= TICK_DELTA && !self.game_over {
+            delta -= TICK_DELTA;
+            self.tick();
+        }
+        self.delta = delta;
+    }
+
+    /// Advances the engine by exactly one step of `TICK_DELTA`
+    pub fn tick(&mut self) {
+        if self.game_over {
+            return;
+        }
+        let dt = TICK_DELTA;
+        self.ticks += 1;
+        self.gravity_delta += dt;
+
+        match self.leveling {
+            LevelingType::Online(ref mut online) => online.update(dt),
+            LevelingType::Ultra(ref ultra) if ultra.is_done(self.ticks as f64 * dt) => {
+                self.set_finished();
+                return;
+            }
+            _ => {}
+        }
+
+        match self.piece {
+            Piece(ref piece) => {
+                let mut piece = piece.clone();
+                for _ in 0..(self.gravity_delta / self.leveling.get_gravity()).floor() as usize {
+                    self.gravity_delta = 0.;
+                    piece = match self.step_piece(piece.clone(), Dir::Down) {
+                        Some(p) => p,
+                        None => break,
+                    };
+                }
+                if self.on_ground(&piece) {
+                    // gravity doesn't build up while resting
+                    self.gravity_delta = 0.;
+                }
+                let lock = match self.lock_delay {
+                    Some(ref mut lock_delay) => {
+                        lock_delay.update(dt);
+                        lock_delay.should_lock()
+                    }
+                    None => false,
+                };
+                if lock {
+                    self.add_piece(piece);
+                }
+            }
+            NoPiece(ref mut time_elapsed, ref time_to_wait) => {
+                *time_elapsed += dt;
+
+                if *time_elapsed > *time_to_wait {
+                    self.add_new_piece();
+                    self.gravity_delta = 0.;
+                }
+            }
+        }
+
+        if let Some((mut ddelta, indices)) = self.destroying_rows_indices.clone() {
+            ddelta += dt;
+
+            let line_clear = self.leveling.timings().line_clear;
+            let hide = DESTROY_HIDE_DELTA.min(line_clear / 2.);
+            if ddelta > line_clear {
+                self.drop_blocks(indices);
+                self.destroying_rows_indices = None;
+                self.refill_dig();
+            } else {
+                if ddelta >= hide && ddelta - dt < hide {
+                    self.animate_destroying_invis(&indices);
+                }
+                self.destroying_rows_indices = Some((ddelta, indices));
+            }
+        }
+    }
+
+    /// Applies an input to the falling piece and returns whether it did anything.
+    /// Only those inputs are recorded, the others can't change how a replay plays out.
+    pub fn input(&mut self, input: Input) -> bool {
+        if self.game_over {
+            return false;
+        }
+        let piece = match self.piece.clone() {
+            Piece(piece) => piece,
+            NoPiece(..) => return false,
+        };
+        let changed = match input {
+            Input::HardDrop => {
+                if let Some(ghost) = self.ghost_piece() {
+                    let cells = (ghost.offset.y - piece.offset.y) as usize;
+                    self.leveling.dropped(cells, true);
+                }
+                self.step_piece(piece, Dir::Up);
+                true
+            }
+            Input::SoftDrop => {
+                let moved = self.step_piece(piece, Dir::Down).is_some();
+                if moved {
+                    self.leveling.dropped(1, false);
+                }
+                moved
+            }
+            Input::MoveLeft => self.step_piece(piece, Dir::Left).is_some(),
+            Input::MoveRight => self.step_piece(piece, Dir::Right).is_some(),
+            Input::RotateCCW => self.rotate_piece(RDir::CClockwise),
+            Input::RotateCW => self.rotate_piece(RDir::Clockwise),
+            Input::Rotate180 => {
+                self.config.rotation_system.has_half_rotation() && self.rotate_piece(RDir::Half)
+            }
+            Input::Hold => self.swap_hold(),
+        };
+        if changed {
+            self.recording.push((self.ticks, ReplayEvent::Input(input)));
+        }
+        changed
+    }
+
+    /// Returns the events since the last call and forgets them
+    pub fn take_events(&mut self) -> Vec<EngineEvent> {
+        std::mem::replace(&mut self.events, vec![])
+    }
+
+    /// Queues garbage that gets inserted once a piece locks without clearing lines
+    pub fn add_garbage_lines(&mut self, amount: usize) {
+        self.recording
+            .push((self.ticks, ReplayEvent::Garbage(amount)));
+        self.garbage_to_add += amount;
+    }
+
+    /// Garbage lines waiting for the next piece to lock
+    pub fn pending_garbage(&self) -> usize {
+        self.garbage_to_add
+    }
+
+    pub fn field(&self) -> &PlayingField {
+        &self.field
+    }
+
+    pub fn piece(&self) -> Option<&Piece> {
+        if let Piece(ref piece) = self.piece {
+            Some(piece)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the current piece moved down as far as it can go
+    pub fn ghost_piece(&self) -> Option<Piece> {
+        let mut ghost_piece = self.piece()?.clone();
+        while !self.field.colliding(&ghost_piece) {
+            ghost_piece.step(Dir::Down);
+        }
+        ghost_piece.step(Dir::Up);
+        Some(ghost_piece)
+    }
+
+    pub fn hold_piece(&self) -> Option<&HoldPiece> {
+        self.hold_piece.as_ref()
+    }
+
+    pub fn next_pieces(&self) -> Vec<Piece> {
+        let mut pieces = self.next_pieces.as_vec();
+        if let Some(left) = self.pieces_left() {
+            pieces.truncate(left);
+        }
+        pieces
+    }
+
+    /// Pieces left in a puzzle's queue, None if they never run out
+    pub fn pieces_left(&self) -> Option<usize> {
+        match (&self.leveling, &self.config.position) {
+            (LevelingType::Puzzle(_), Some(position)) => Some(
+                position
+                    .queue
+                    .len()
+                    .saturating_sub(self.next_pieces.taken()),
+            ),
+            _ => None,
+        }
+    }
+
+    pub fn leveling(&self) -> &LevelingType {
+        &self.leveling
+    }
+
+    pub fn config(&self) -> &EngineConfig {
+        &self.config
+    }
+
+    /// Returns the seed this game was started with
+    pub fn seed(&self) -> u64 {
+        self.next_pieces.seed()
+    }
+
+    pub fn is_game_over(&self) -> bool {
+        self.game_over
+    }
+
+    /// Whether the game ended by reaching the goal of its mode
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Seconds played, including the time not yet consumed by a tick, so a game
+    /// ended by an input ends to the frame. For clocks and records, a replay
+    /// only sees whole ticks.
+    pub fn time(&self) -> f64 {
+        self.end_time
+            .unwrap_or_else(|| self.game_time() + self.delta)
+    }
+
+    /// Seconds played in whole ticks, the same live and in a replay.
+    /// Everything that affects the game goes by this.
+    pub fn game_time(&self) -> f64 {
+        self.ticks as f64 * TICK_DELTA
+    }
+
+    /// Pieces locked since the start
+    pub fn pieces(&self) -> usize {
+        self.pieces
+    }
+
+    /// Ticks played since the start
+    pub fn ticks(&self) -> u64 {
+        self.ticks
+    }
+
+    pub fn recording(&self) -> &[(u64, ReplayEvent)] {
+        &self.recording
+    }
+
+    fn set_game_over(&mut self) {
+        if !self.game_over {
+            self.game_over = true;
+            self.end_time = Some(self.time());
+            self.events.push(EngineEvent::GameOver);
+        }
+    }
+
+    fn set_finished(&mut self) {
+        if !self.game_over {
+            self.game_over = true;
+            self.end_time = Some(self.time());
+            self.finished = true;
+            self.events.push(EngineEvent::Finished);
+        }
+    }
+
+    fn add_new_piece(&mut self) {
+        let mut piece = if self.pieces_left() == Some(0) {
+            // the last piece of a puzzle may still be in the hold
+            match self.hold_piece.take() {
+                Some(HoldPiece::Unlocked(piece)) => piece,
+                hold_piece => {
+                    self.hold_piece = hold_piece;
+                    self.set_game_over();
+                    return;
+                }
+            }
+        } else {
+            self.next_pieces.get()
+        };
+        piece.move_to_spawn(self.field.width());
+        self.last_rotation = None;
+        self.leveling.piece_spawned();
+        piece.step(Dir::Down);
+        if self.field.colliding(&piece) {
+            piece.step(Dir::Up);
+            // blocked by the stack, or on a field too low for it
+            if self.field.colliding(&piece) {
+                self.set_game_over();
+            }
+        }
+        self.set_piece(piece);
+    }
+
+    /// Makes `piece` the falling piece, with a fresh lock delay
+    fn set_piece(&mut self, piece: Piece) {
+        let piece = self.apply_instant_gravity(piece);
+        let lock_down = self.leveling.lock_down().unwrap_or(self.config.lock_down);
+        let delay = self.leveling.timings().lock;
+        let on_ground = self.on_ground(&piece);
+        self.lock_delay = Some(LockDelay::new(lock_down, delay, piece.offset.y, on_ground));
+        self.piece = Piece(piece);
+    }
+
+    /// Makes `piece` the falling piece after it moved successfully and returns where it ended up
+    fn move_piece(&mut self, piece: Piece) -> Piece {
+        let piece = self.apply_instant_gravity(piece);
+        let on_ground = self.on_ground(&piece);
+        if let Some(ref mut lock_delay) = self.lock_delay {
+            lock_delay.moved(piece.offset.y, on_ground);
+        }
+        self.piece = Piece(piece.clone());
+        piece
+    }
+
+    /// At 20G the piece is always on the floor, so it is put there right away
+    /// instead of falling row by row
+    fn apply_instant_gravity(&self, piece: Piece) -> Piece {
+        if self.leveling.get_gravity() > seconds_per_row(GRAVITY_20G) {
+            return piece;
+        }
+        let mut piece = piece;
+        while !self.field.colliding(&piece) {
+            piece.step(Dir::Down);
+        }
+        piece.step(Dir::Up);
+        piece
+    }
+
+    fn on_ground(&self, piece: &Piece) -> bool {
+        let mut below = piece.clone();
+        below.step(Dir::Down);
+        self.field.colliding(&below)
+    }
+
+    fn add_one_garbage_line(&mut self) {
+        let block = Block::new(BlockColor::Garbage);
+        let width = self.field.width();
+        let mut row = vec![Some(block); width];
+        row[self.garbage_rng.gen_range(0, width)] = None;
+        if self.field.add_row(self.field.height(), row) {
+            self.set_game_over();
+        }
+    }
+
+    /// Adds a row of dig garbage whose hole moves with a chance of `dig_messiness`
+    fn add_dig_row(&mut self) {
+        let messiness = self.config.dig_messiness.max(0.).min(1.);
+        let width = self.field.width();
+        if self.garbage_rng.gen_bool(messiness) {
+            // any column but the current one
+            self.dig_hole = (self.dig_hole + self.garbage_rng.gen_range(1, width)) % width;
+        }
+        let block = Block::new(BlockColor::Garbage);
+        let mut row = vec![Some(block); width];
+        row[self.dig_hole] = None;
+        if self.field.add_row(self.field.height(), row) {
+            self.set_game_over();
+        }
+    }
+
+    /// Tops the dig garbage up from below
+    fn refill_dig(&mut self) {
+        let rows = match self.leveling {
+            LevelingType::Dig(ref mut dig) => dig.refill(),
+            _ => return,
+        };
+        for _ in 0..rows {
+            self.add_dig_row();
+        }
+    }
+
+    /// Returns whether the piece was swapped
+    fn swap_hold(&mut self) -> bool {
+        use HoldPiece::*;
+        if let Piece(mut old_piece) = self.piece.clone() {
+            if let Some(Unlocked(mut new_piece)) = self.hold_piece.clone() {
+                new_piece.move_to_spawn(self.field.width());
+                self.set_piece(new_piece);
+                self.last_rotation = None;
+                // Reset piece state
+                old_piece.reset();
+                self.hold_piece = Some(Locked(old_piece));
+                return true;
+            } else if self.hold_piece.is_none() && self.pieces_left() != Some(0) {
+                self.add_new_piece();
+                old_piece.reset();
+                self.hold_piece = Some(Locked(old_piece));
+                return true;
+            }
+        }
+        false
+    }
+
+    fn animate_destroying_invis(&mut self, indices: &[usize]) {
+        for index in indices {
+            self.field.set_row(*index, self.field.empty_row());
+        }
+    }
+
+    fn drop_blocks(&mut self, indices_unsorted: Vec<usize>) {
+        let mut field_cpy = self.field.to_vec();
+        let empty_row = self.field.empty_row();
+        let mut indices = indices_unsorted;
+        indices.sort();
+        for index in indices {
+            field_cpy.remove(index);
+            field_cpy.insert(0, empty_row.clone());
+        }
+        for (y, row) in field_cpy.into_iter().enumerate() {
+            self.field.set_row(y, row);
+        }
+    }
+
+    /// Returns the modified piece if it still exists
+    fn step_piece(&mut self, piece: Piece, dir: Dir) -> Option<Piece> {
+        let mut piece = piece;
+        let before_piece = piece.clone();
+
+        match dir {
+            Dir::Left | Dir::Right | Dir::Down => {
+                piece.step(dir);
+                if !self.field.colliding(&piece) {
+                    self.last_rotation = None;
+                    Some(self.move_piece(piece))
+                } else {
+                    None
+                }
+            }
+            Dir::Up => {
+                while !self.field.colliding(&piece) {
+                    piece.step(Dir::Down);
+                }
+                piece.step(Dir::Up);
+                if piece.offset.y != before_piece.offset.y {
+                    self.last_rotation = None;
+                }
+                self.add_piece(piece);
+                None
+            }
+        }
+    }
+
+    /// Returns whether one of the kicks fit
+    fn rotate_piece(&mut self, rdir: RDir) -> bool {
+        if let Piece(mut piece) = self.piece.clone() {
+            let start_rot = piece.get_rotation();
+            let end_rot = piece.get_rotation() + rdir;
+            piece.set_rotation(end_rot.clone());
+
+            let kicks = self
+                .config
+                .rotation_system
+                .kicks(&piece.shape, &start_rot, &end_rot);
+            for (i, kick) in kicks.into_iter().enumerate() {
+                let mut moved_piece = piece.clone();
+                moved_piece.offset = moved_piece.offset + kick;
+                if !self.field.colliding(&moved_piece) {
+                    self.move_piece(moved_piece);
+                    self.last_rotation = Some((rdir, i));
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    fn add_piece(&mut self, piece: Piece) {
+        use HoldPiece::*;
+        if let Some(Locked(piece)) = &self.hold_piece {
+            self.hold_piece = Some(Unlocked(piece.clone()));
+        }
+
+        self.lock_delay = None;
+        let spin = self.detect_spin(&piece);
+        let should_game_over = self.field.add_piece(piece);
+        self.events.push(EngineEvent::PieceLocked);
+        self.pieces += 1;
+
+        let garbage_cleared = self.full_garbage_rows();
+        let destroyed_rows_indices = self.check_rows_destroying();
+        if let LevelingType::Dig(ref mut dig) = self.leveling {
+            dig.cleared_garbage(garbage_cleared);
+            if dig.is_done() {
+                self.set_finished();
+            }
+        }
+        let mut clear = LineClear {
+            lines: destroyed_rows_indices.len(),
+            spin,
+            combo: 0,
+            back_to_back: false,
+            perfect_clear: self.field.perfect_clear(&destroyed_rows_indices),
+        };
+        if clear.lines > 0 {
+            self.combo = Some(self.combo.map_or(0, |combo| combo + 1));
+            clear.combo = self.combo.unwrap_or(0);
+            clear.back_to_back = clear.is_difficult() && self.back_to_back;
+            self.back_to_back = clear.is_difficult();
+        } else {
+            self.combo = None;
+        }
+        if clear.lines > 0 || clear.spin != Spin::None {
+            self.destroyed_lines(clear);
+        }
+        if clear.lines > 0 {
+            // outgoing attack cancels incoming garbage first
+            let attack = self.config.attack_table.attack(&clear);
+            let cancelled = attack.min(self.garbage_to_add);
+            self.garbage_to_add -= cancelled;
+            if attack > cancelled {
+                self.events.push(EngineEvent::Attack(attack - cancelled));
+            }
+        } else {
+            for _ in 0..self.garbage_to_add {
+                self.add_one_garbage_line();
+            }
+            self.garbage_to_add = 0;
+        }
+        let timings = self.leveling.timings();
+        let time_to_wait = if destroyed_rows_indices.is_empty() {
+            timings.are
+        } else {
+            timings.line_clear + timings.line_are
+        };
+        self.piece = PieceState::NoPiece(0., time_to_wait);
+        if should_game_over && destroyed_rows_indices.is_empty() {
+            // top out with no rows about to be destroyed / marked destroying
+            self.set_game_over();
+        }
+    }
+
+    /// Applies the 3-corner rule to a piece that is about to lock
+    fn detect_spin(&self, piece: &Piece) -> Spin {
+        use RotationState::*;
+        let (rdir, kick) = match self.last_rotation {
+            Some(last_rotation) if piece.shape.spins() => last_rotation,
+            _ => return Spin::None,
+        };
+        let (x, y): (isize, isize) = piece.offset.into();
+        // clockwise from top left
+        let blocked: Vec<bool> = [(x, y), (x + 2, y), (x + 2, y + 2), (x, y + 2)]
+            .iter()
+            .map(|(x, y)| self.field.cell_blocked(*x, *y))
+            .collect();
+        if blocked.iter().filter(|b| **b).count() < 3 {
+            return Spin::None;
+        }
+        // the two corners on the side the T points to
+        let front = match piece.get_rotation() {
+            Rs0 => [0, 1],
+            Rs1 => [1, 2],
+            Rs2 => [2, 3],
+            Rs3 => [3, 0],
+        };
+        let tst_kick = kick == TST_KICK
+            && self.config.rotation_system == RotationSystem::Srs
+            && !matches!(rdir, RDir::Half);
+        if front.iter().all(|i| blocked[*i]) || tst_kick {
+            Spin::Full
+        } else {
+            Spin::Mini
+        }
+    }
+
+    /// Counts the full rows that contain garbage
+    fn full_garbage_rows(&self) -> usize {
+        self.field
+            .to_vec()
+            .iter()
+            .filter(|row| !row.contains(&None))
+            .filter(|row| {
+                row.iter()
+                    .flatten()
+                    .any(|block| block.block_color() == BlockColor::Garbage)
+            })
+            .count()
+    }
+
+    /// returns Vec <index of rows that need to be destroyed>
+    fn check_rows_destroying(&mut self) -> Vec<usize> {
+        let mut row_indices_to_destroy: Vec<usize> = vec![];
+        let field_cpy = self.field.clone();
+        let row_destroying = vec![Some(Block::new(BlockColor::Destroying)); self.field.width()];
+
+        for (y, row) in field_cpy.to_vec().iter().enumerate() {
+            if !row.contains(&None) {
+                row_indices_to_destroy.push(y);
+                self.field.set_row(y, row_destroying.clone());
+            }
+        }
+
+        if !row_indices_to_destroy.is_empty() {
+            self.destroying_rows_indices = Some((0., row_indices_to_destroy.clone()));
+        }
+        row_indices_to_destroy
+    }
+
+    fn destroyed_lines(&mut self, clear: LineClear) {
+        self.events.push(EngineEvent::LinesCleared(clear));
+        // splits, section times and the grand master limits must come out
+        // the same in a replay, so they go by whole ticks
+        let time = self.game_time();
+        match self.leveling {
+            LevelingType::Single(ref mut single) => {
+                single.cleared_lines(clear);
+                if single.is_done() {
+                    self.set_finished();
+                }
+            }
+            LevelingType::Ultra(ref mut ultra) => ultra.scoring.cleared_lines(clear),
+            LevelingType::Sprint(ref mut sprint) => {
+                sprint.cleared_lines(clear.lines, time);
+                if sprint.is_done() {
+                    self.set_finished();
+                }
+            }
+            LevelingType::Puzzle(ref mut puzzle) => {
+                puzzle.cleared_lines(clear);
+                if puzzle.is_solved() {
+                    self.set_finished();
+                }
+            }
+            LevelingType::Master(ref mut master) => {
+                master.cleared_lines(clear, time);
+                if master.is_done() {
+                    self.set_finished();
+                }
+            }
+            LevelingType::Online(_) | LevelingType::Dig(_) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::Position;
+
+    /// Starts a game on `rows` and puts the piece `letter` at `x`, `row` rows
+    /// below the top of `rows`
+    fn engine_with_piece(
+        rows: &[&str],
+        letter: char,
+        x: isize,
+        row: usize,
+        rotation: RotationState,
+    ) -> Engine {
+        let mut config = EngineConfig::new();
+        let shape = config.piece_set.shape(letter).unwrap();
+        let mut position = Position::new();
+        position.field = PlayingField::from_rows(rows, BoardSize::default()).unwrap();
+        position.queue = vec![shape.clone()];
+        config.position = Some(position);
+        let mut engine = Engine::new(LevelingType::new(false, GameMode::Marathon), config);
+        engine.tick();
+
+        let mut piece = Piece::new(shape);
+        let top = engine.field().visible_height() - rows.len();
+        piece.offset = (x, (top + row) as isize).into();
+        piece.set_rotation(rotation);
+        assert!(!engine.field().colliding(&piece));
+        engine.set_piece(piece);
+        engine
+    }
+
+    /// Puts a T at `x`, `row` rows below the top of `rows`, turns it clockwise
+    /// and drops it, returning what the lock counted as
+    fn rotate_t_into(rows: &[&str], x: isize, row: usize, rotation: RotationState) -> LineClear {
+        let mut engine = engine_with_piece(rows, 'T', x, row, rotation);
+        assert!(engine.input(Input::RotateCW));
+        assert!(engine.input(Input::HardDrop));
+        engine
+            .take_events()
+            .into_iter()
+            .find_map(|event| match event {
+                EngineEvent::LinesCleared(clear) => Some(clear),
+                _ => None,
+            })
+            .expect("the T didn't count as a spin or clear lines")
+    }
+
+    #[test]
+    fn t_spin_double() {
+        let rows = ["..........", "#.........", "...#######", "#.########"];
+        let clear = rotate_t_into(&rows, 0, 1, RotationState::Rs1);
+        assert_eq!(clear.spin, Spin::Full);
+        assert_eq!(clear.lines, 2);
+    }
+
+    #[test]
+    fn t_spin_mini() {
+        let rows = ["....######", ".#..######", "#...######"];
+        let clear = rotate_t_into(&rows, 2, 0, RotationState::Rs3);
+        assert_eq!(clear.spin, Spin::Mini);
+        assert_eq!(clear.lines, 1);
+    }
+
+    #[test]
+    fn tst_kick_makes_a_mini_a_full_spin() {
+        // only one corner in front of the T is filled, but it got there with the last kick
+        let rows = [
+            "##........",
+            "#.........",
+            "#.########",
+            "#..#######",
+            "#..#######",
+        ];
+        let clear = rotate_t_into(&rows, 1, 0, RotationState::Rs0);
+        assert_eq!(clear.spin, Spin::Full);
+        assert_eq!(clear.lines, 2);
+    }
+
+    #[test]
+    fn piece_spawning_below_the_floor_tops_out() {
+        let mut config = EngineConfig::new();
+        // the grid's top on the last row puts the I's blocks a row below it
+        let text = format!(
+            "name: I\npiece: I cyan kicks=i spawn={}\n....\n####\n....\n....",
+            FIELD_HEIGHT_MIN - 1
+        );
+        config.piece_set = PieceSet::from_text(&text).unwrap();
+        config.board = BoardSize {
+            width: 10,
+            height: FIELD_HEIGHT_MIN,
+        };
+        assert!(config.board.check().is_ok());
+        let mut engine = Engine::new(LevelingType::new(false, GameMode::Marathon), config);
+        for _ in 0..600 {
+            engine.tick();
+        }
+        assert!(engine.is_game_over());
+    }
+
+    #[test]
+    fn attack_cancels_pending_garbage_first() {
+        let rows = [
+            "#.........",
+            "#########.",
+            "#########.",
+            "#########.",
+            "#########.",
+        ];
+        // a vertical I in the last column makes a tetris, worth 4 rows
+        let tetris = |garbage| {
+            let mut engine = engine_with_piece(&rows, 'I', 7, 1, RotationState::Rs1);
+            engine.add_garbage_lines(garbage);
+            assert!(engine.input(Input::HardDrop));
+            let attack = engine
+                .take_events()
+                .into_iter()
+                .find_map(|event| match event {
+                    EngineEvent::Attack(rows) => Some(rows),
+                    _ => None,
+                });
+            (attack, engine.pending_garbage())
+        };
+        assert_eq!(tetris(0), (Some(4), 0));
+        assert_eq!(tetris(3), (Some(1), 0));
+        assert_eq!(tetris(4), (None, 0));
+        assert_eq!(tetris(6), (None, 2));
+    }
+}
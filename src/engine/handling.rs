@@ -0,0 +1,206 @@
+use super::{Engine, Input};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// How held keys repeat. Every player can tune these to their liking.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default = "Handling::default")]
+pub struct Handling {
+    /// Delayed auto shift: how long a direction is held before it repeats, in seconds
+    pub das: f64,
+    /// Auto repeat rate: time between repeated moves, 0 moves to the wall at once
+    pub arr: f64,
+    /// DAS cut delay: pause of the auto shift after a rotation, hold or hard drop
+    pub dcd: f64,
+    /// How many times faster than gravity a held soft drop falls, None drops instantly
+    #[serde(
+        default = "default_soft_drop_factor",
+        serialize_with = "serialize_soft_drop_factor",
+        deserialize_with = "deserialize_soft_drop_factor"
+    )]
+    pub soft_drop_factor: Option<f64>,
+}
+
+impl Handling {
+    pub fn default() -> Handling {
+        Handling {
+            das: 0.182,
+            arr: 0.05,
+            dcd: 0.,
+            soft_drop_factor: default_soft_drop_factor(),
+        }
+    }
+}
+
+fn default_soft_drop_factor() -> Option<f64> {
+    Some(20.)
+}
+
+/// TOML can't write None, so an instant soft drop is saved as an infinite factor
+fn serialize_soft_drop_factor<S: Serializer>(
+    factor: &Option<f64>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    factor.unwrap_or(f64::INFINITY).serialize(serializer)
+}
+
+fn deserialize_soft_drop_factor<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<f64>, D::Error> {
+    let factor = f64::deserialize(deserializer)?;
+    Ok(Some(factor).filter(|f| f.is_finite()))
+}
+
+/// Turns held inputs into repeated engine inputs according to a `Handling`.
+/// The timers keep running while there is no piece, so DAS charges during
+/// entry and line clear delay and applies as soon as the next piece spawns.
+#[derive(Clone, Debug)]
+pub struct InputRepeater {
+    handling: Handling,
+    /// Held directions, the last pressed one is at the end
+    held_dirs: Vec<Input>,
+    das_delta: f64,
+    arr_delta: f64,
+    cut_delta: f64,
+    soft_drop: Option<f64>, // time since the last soft drop step while held
+}
+
+impl InputRepeater {
+    pub fn new(handling: Handling) -> InputRepeater {
+        InputRepeater {
+            handling,
+            held_dirs: vec![],
+            das_delta: 0.,
+            arr_delta: 0.,
+            cut_delta: 0.,
+            soft_drop: None,
+        }
+    }
+
+    pub fn handling(&self) -> &Handling {
+        &self.handling
+    }
+
+    /// Applies a freshly pressed input and starts repeating it if it repeats.
+    /// Returns whether the input did anything.
+    pub fn press(&mut self, input: Input, engine: &mut Engine) -> bool {
+        match input {
+            Input::MoveLeft | Input::MoveRight => {
+                self.held_dirs.retain(|i| *i != input);
+                self.held_dirs.push(input);
+                self.das_delta = 0.;
+                self.arr_delta = 0.;
+            }
+            Input::SoftDrop => {
+                self.soft_drop = Some(0.);
+                if self.handling.soft_drop_factor.is_none() {
+                    return drop_to_floor(engine);
+                }
+            }
+            Input::RotateCW
+            | Input::RotateCCW
+            | Input::Rotate180
+            | Input::Hold
+            | Input::HardDrop => {
+                self.cut_delta = self.handling.dcd;
+            }
+        }
+        engine.input(input)
+    }
+
+    pub fn release(&mut self, input: Input) {
+        match input {
+            Input::MoveLeft | Input::MoveRight => {
+                self.held_dirs.retain(|i| *i != input);
+                self.das_delta = 0.;
+                self.arr_delta = 0.;
+            }
+            Input::SoftDrop => self.soft_drop = None,
+            _ => {}
+        }
+    }
+
+    /// Forgets every held input, e.g. when the game is paused
+    pub fn release_all(&mut self) {
+        self.held_dirs.clear();
+        self.soft_drop = None;
+    }
+
+    pub fn update(&mut self, dt: f64, engine: &mut Engine) {
+        self.cut_delta = (self.cut_delta - dt).max(0.);
+
+        if let Some(dir) = self.held_dirs.last().cloned() {
+            self.das_delta += dt;
+            if self.das_delta >= self.handling.das && self.cut_delta <= 0. {
+                if self.handling.arr <= 0. {
+                    shift_to_wall(engine, dir);
+                } else {
+                    self.arr_delta += dt;
+                    while self.arr_delta >= self.handling.arr {
+                        self.arr_delta -= self.handling.arr;
+                        engine.input(dir);
+                    }
+                }
+            }
+        }
+
+        if let Some(ref mut soft_drop_delta) = self.soft_drop {
+            match self.handling.soft_drop_factor {
+                Some(factor) => {
+                    let interval = engine.leveling().get_gravity() / factor;
+                    *soft_drop_delta += dt;
+                    // at high gravity this is many rows a frame, but it stops at the floor
+                    while *soft_drop_delta >= interval {
+                        *soft_drop_delta -= interval;
+                        if !engine.input(Input::SoftDrop) {
+                            *soft_drop_delta = 0.;
+                            break;
+                        }
+                    }
+                }
+                None => {
+                    drop_to_floor(engine);
+                }
+            }
+        }
+    }
+}
+
+/// Repeats a move until the piece stops moving. Returns whether it moved at all.
+fn repeat_while_moving(engine: &mut Engine, input: Input) -> bool {
+    let mut moved = false;
+    while engine.input(input) {
+        moved = true;
+    }
+    moved
+}
+
+fn shift_to_wall(engine: &mut Engine, dir: Input) {
+    repeat_while_moving(engine, dir);
+}
+
+fn drop_to_floor(engine: &mut Engine) -> bool {
+    repeat_while_moving(engine, Input::SoftDrop)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn soft_drop_factor_round_trips() {
+        for factor in [Some(20.), None].iter() {
+            let handling = Handling {
+                soft_drop_factor: *factor,
+                ..Handling::default()
+            };
+            let text = toml::to_string(&handling).unwrap();
+            assert_eq!(toml::from_str::<Handling>(&text).unwrap(), handling);
+        }
+    }
+
+    #[test]
+    fn missing_soft_drop_factor_is_the_default() {
+        let handling: Handling = toml::from_str("das = 0.1").unwrap();
+        assert_eq!(handling.soft_drop_factor, Some(20.));
+    }
+}
@@ -0,0 +1,15 @@
+use serde::{Deserialize, Serialize};
+
+/// Everything a player can do to the engine. Scenes translate raw key and
+/// button presses into these before handing them to `Engine::input`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Input {
+    MoveLeft,
+    MoveRight,
+    SoftDrop,
+    HardDrop,
+    RotateCW,
+    RotateCCW,
+    Rotate180,
+    Hold,
+}
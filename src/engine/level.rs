@@ -0,0 +1,817 @@
+use super::scoring::{LineClear, Spin};
+use crate::util::LockDown;
+use serde::{Deserialize, Serialize};
+
+/// Lines between two splits of a sprint
+pub const SPRINT_SPLIT_LINES: usize = 10;
+/// Garbage rows kept on the field in dig mode while there are any left
+pub const DIG_VISIBLE_ROWS: usize = 10;
+/// Levels in a section of master mode
+pub const MASTER_SECTION_LEVELS: usize = 100;
+/// The level that finishes master mode
+pub const MASTER_LEVEL_MAX: usize = 999;
+/// Gravity from each level on in master mode, in 256ths of a G
+const MASTER_GRAVITY: [(usize, u32); 30] = [
+    (0, 4),
+    (30, 6),
+    (35, 8),
+    (40, 10),
+    (50, 12),
+    (60, 16),
+    (70, 32),
+    (80, 48),
+    (90, 64),
+    (100, 80),
+    (120, 96),
+    (140, 112),
+    (160, 128),
+    (170, 144),
+    (200, 4),
+    (220, 32),
+    (230, 64),
+    (233, 96),
+    (236, 128),
+    (239, 160),
+    (243, 192),
+    (247, 224),
+    (251, 256),
+    (300, 512),
+    (330, 768),
+    (360, 1024),
+    (400, 1280),
+    (420, 1024),
+    (450, 768),
+    (500, 5120),
+];
+/// Frames of ARE, line ARE, line clear delay and lock delay from each level on in master mode
+const MASTER_TIMINGS: [(usize, [u32; 4]); 6] = [
+    (0, [25, 25, 40, 30]),
+    (500, [25, 25, 25, 30]),
+    (600, [25, 16, 16, 30]),
+    (700, [16, 12, 12, 30]),
+    (800, [12, 6, 6, 30]),
+    (900, [12, 6, 6, 17]),
+];
+/// Score needed for each grade in master mode, from the lowest
+const MASTER_GRADES: [(&str, usize); 18] = [
+    ("9", 0),
+    ("8", 400),
+    ("7", 800),
+    ("6", 1400),
+    ("5", 2000),
+    ("4", 3500),
+    ("3", 5500),
+    ("2", 8000),
+    ("1", 12000),
+    ("S1", 16000),
+    ("S2", 22000),
+    ("S3", 30000),
+    ("S4", 40000),
+    ("S5", 52000),
+    ("S6", 66000),
+    ("S7", 82000),
+    ("S8", 100000),
+    ("S9", 120000),
+];
+/// Level, score and seconds to reach on the way to the grand master grade
+const MASTER_GM_CHECKS: [(usize, usize, f64); 3] = [
+    (300, 12000, 255.),
+    (500, 40000, 450.),
+    (MASTER_LEVEL_MAX, 126000, 810.),
+];
+
+/// The single player modes to choose from in the menu
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum GameMode {
+    /// The guideline levels, ending after the last one
+    Marathon,
+    /// The guideline levels up to 20G, without an end
+    Endless,
+    /// Ends once this many lines are cleared
+    Sprint(usize),
+    /// Highest score in this many seconds
+    Ultra(u32),
+    /// Ends once this many garbage rows are cleared
+    Dig(usize),
+    /// Starts from a given position and ends once the goal is reached or the pieces run out
+    Puzzle(PuzzleGoal),
+    /// Levels up to 999 with pieces and lines, reaching 20G and graded by score
+    Master,
+}
+
+impl GameMode {
+    pub fn all() -> Vec<GameMode> {
+        use GameMode::*;
+        vec![
+            Marathon,
+            Endless,
+            Sprint(40),
+            Sprint(20),
+            Sprint(100),
+            Ultra(120),
+            Ultra(180),
+            Dig(10),
+            Dig(18),
+            Dig(100),
+            Master,
+        ]
+    }
+
+    pub fn name(self) -> String {
+        match self {
+            GameMode::Marathon => "Marathon".to_string(),
+            GameMode::Endless => "Endless".to_string(),
+            GameMode::Sprint(lines) => format!("Sprint {}L", lines),
+            GameMode::Ultra(secs) => format!("Ultra {}min", secs / 60),
+            GameMode::Dig(rows) => format!("Dig {}L", rows),
+            GameMode::Puzzle(_) => "Puzzle".to_string(),
+            GameMode::Master => "Master".to_string(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum PuzzleGoal {
+    Lines(usize),
+    PerfectClear,
+    TSpinTriple,
+}
+
+impl PuzzleGoal {
+    pub fn name(self) -> String {
+        match self {
+            PuzzleGoal::Lines(1) => "Clear a line".to_string(),
+            PuzzleGoal::Lines(lines) => format!("Clear {} lines", lines),
+            PuzzleGoal::PerfectClear => "Perfect clear".to_string(),
+            PuzzleGoal::TSpinTriple => "T-spin triple".to_string(),
+        }
+    }
+
+    /// Reads `lines <n>`, `perfect clear` or `tst`
+    pub fn from_text(text: &str) -> Result<PuzzleGoal, String> {
+        let words: Vec<String> = text
+            .split_whitespace()
+            .map(|w| w.to_ascii_lowercase())
+            .collect();
+        let words: Vec<&str> = words.iter().map(String::as_str).collect();
+        match words.as_slice() {
+            ["lines", n] => n
+                .parse()
+                .map(PuzzleGoal::Lines)
+                .map_err(|_| format!("'{}' is not a number of lines", n)),
+            ["perfect", "clear"] | ["pc"] => Ok(PuzzleGoal::PerfectClear),
+            ["t-spin", "triple"] | ["tst"] => Ok(PuzzleGoal::TSpinTriple),
+            _ => Err(format!("Unknown goal '{}'", text)),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum LevelingType {
+    Online(Online),
+    Single(Singleplayer),
+    Sprint(Sprint),
+    Ultra(Ultra),
+    Dig(Dig),
+    Puzzle(PuzzleProgress),
+    Master(Master),
+}
+
+impl LevelingType {
+    pub fn online() -> LevelingType {
+        LevelingType::Online(Online::new())
+    }
+    pub fn new(online: bool, mode: GameMode) -> LevelingType {
+        if online {
+            return LevelingType::online();
+        }
+        match mode {
+            GameMode::Marathon => LevelingType::Single(Singleplayer::new(LevelTable::marathon())),
+            GameMode::Endless => LevelingType::Single(Singleplayer::new(LevelTable::endless())),
+            GameMode::Sprint(goal) => LevelingType::Sprint(Sprint::new(goal)),
+            GameMode::Ultra(secs) => LevelingType::Ultra(Ultra::new(secs)),
+            GameMode::Dig(rows) => LevelingType::Dig(Dig::new(rows)),
+            GameMode::Puzzle(goal) => LevelingType::Puzzle(PuzzleProgress::new(goal)),
+            GameMode::Master => LevelingType::Master(Master::new()),
+        }
+    }
+
+    /// Awards points for cells the player dropped the piece by
+    pub fn dropped(&mut self, cells: usize, hard: bool) {
+        use LevelingType::*;
+        match self {
+            Single(single) => single.dropped(cells, hard),
+            Ultra(ultra) => ultra.scoring.dropped(cells, hard),
+            Master(master) => master.dropped(cells, hard),
+            Online(_) | Sprint(_) | Dig(_) | Puzzle(_) => {}
+        }
+    }
+
+    /// Call whenever a new piece appears
+    pub fn piece_spawned(&mut self) {
+        if let LevelingType::Master(master) = self {
+            master.piece_spawned();
+        }
+    }
+
+    pub fn timings(&self) -> Timings {
+        match self {
+            LevelingType::Master(master) => master.timings(),
+            _ => Timings::default(),
+        }
+    }
+
+    /// The lock down the mode plays with, if it doesn't leave it to the player
+    pub fn lock_down(&self) -> Option<LockDown> {
+        match self {
+            LevelingType::Master(_) => Some(LockDown::StepReset),
+            _ => None,
+        }
+    }
+
+    // pub fn get_score(&self, lines: usize) -> usize {
+    //     if let
+
+    // pub fn cleared_lines(&mut self, amount: usize) {
+    //     if let LevelingType::Single(ref mut single) = self {
+    //         single.cleared_lines(amount);
+    //     }
+    // }
+
+    // pub fn update(&mut self, dt: f64) {
+    //     if let LevelingType::Online(ref mut online) = self {
+    //         online.update(dt);
+    //     }
+    // }
+
+    pub fn get_gravity(&self) -> f64 {
+        use LevelingType::*;
+        match self {
+            Single(single) => single.get_gravity(),
+            Online(online) => online.get_gravity(),
+            Master(master) => master.gravity(),
+            Sprint(_) | Ultra(_) | Dig(_) | Puzzle(_) => seconds_per_row(GUIDELINE_GRAVITY[0]),
+        }
+    }
+
+    // pub fn get_level(&self) -> usize {
+    //     use LevelingType::*;
+    //     match self {
+    //         Single(single) => single.get_level(),
+    //         Online(online) => online.get_level(),
+    //     }
+    // }
+}
+
+// pub trait LevelingTypeT {
+//     fn default() -> Self;
+//     fn get_gravity(&self) -> f64;
+//     fn cleared_lines(&mut self);
+// }
+
+#[derive(Clone, Debug)]
+pub struct Online {
+    level: usize,
+    table: LevelTable,
+    time_passed: f64,
+}
+
+impl Online {
+    pub fn new() -> Online {
+        Online {
+            level: 1,
+            table: LevelTable::classic(),
+            time_passed: 0.,
+        }
+    }
+    pub fn update(&mut self, dt: f64) {
+        self.time_passed += dt;
+        let level = 1 + ((self.time_passed + 30.).floor() / 20.).floor() as usize;
+        self.level = level.min(self.table.cap());
+    }
+    pub fn get_gravity(&self) -> f64 {
+        self.table.gravity(self.level)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Singleplayer {
+    score: usize,
+    level: usize,
+    lines: usize,
+    table: LevelTable,
+    /// Whether clearing lines raises the level
+    leveling_up: bool,
+}
+
+impl Singleplayer {
+    fn new(table: LevelTable) -> Self {
+        Self {
+            score: 0,
+            level: 1,
+            lines: 0,
+            table,
+            leveling_up: true,
+        }
+    }
+
+    /// Scores like a marathon but stays at level 1
+    fn fixed_level() -> Self {
+        Self {
+            leveling_up: false,
+            ..Self::new(LevelTable::marathon())
+        }
+    }
+
+    pub fn get_level(&self) -> usize {
+        self.level
+    }
+
+    pub fn get_gravity(&self) -> f64 {
+        self.table.gravity(self.level)
+    }
+
+    pub fn get_score(&self) -> usize {
+        self.score
+    }
+
+    pub fn get_lines(&self) -> usize {
+        self.lines
+    }
+
+    /// Lines that finish the game, if there is an end
+    pub fn goal(&self) -> Option<usize> {
+        if self.leveling_up {
+            self.table.goal()
+        } else {
+            None
+        }
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.goal().map_or(false, |goal| self.lines >= goal)
+    }
+
+    /// Soft drops give a point per cell, hard drops two
+    pub fn dropped(&mut self, cells: usize, hard: bool) {
+        self.score += if hard { cells * 2 } else { cells };
+    }
+
+    fn get_score_from_lines(&self, clear: LineClear) -> usize {
+        clear.score(self.level)
+    }
+
+    pub fn cleared_lines(&mut self, clear: LineClear) {
+        self.score += self.get_score_from_lines(clear);
+        self.lines += clear.lines;
+        if self.leveling_up {
+            self.level = self.table.level_for_lines(self.lines);
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Sprint {
+    goal: usize,
+    lines: usize,
+    /// Time at every `SPRINT_SPLIT_LINES` lines
+    splits: Vec<f64>,
+}
+
+impl Sprint {
+    fn new(goal: usize) -> Sprint {
+        Sprint {
+            goal,
+            lines: 0,
+            splits: vec![],
+        }
+    }
+
+    pub fn goal(&self) -> usize {
+        self.goal
+    }
+
+    pub fn lines(&self) -> usize {
+        self.lines
+    }
+
+    pub fn splits(&self) -> &[f64] {
+        &self.splits
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.lines >= self.goal
+    }
+
+    /// Counts the lines towards the goal, `time` is the game time they were cleared at
+    pub fn cleared_lines(&mut self, lines: usize, time: f64) {
+        let before = self.lines;
+        self.lines = (self.lines + lines).min(self.goal);
+        for _ in before / SPRINT_SPLIT_LINES..self.lines / SPRINT_SPLIT_LINES {
+            self.splits.push(time);
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Ultra {
+    /// Length of the game in seconds
+    duration: u32,
+    pub scoring: Singleplayer,
+}
+
+impl Ultra {
+    fn new(duration: u32) -> Ultra {
+        Ultra {
+            duration,
+            scoring: Singleplayer::fixed_level(),
+        }
+    }
+
+    pub fn duration(&self) -> u32 {
+        self.duration
+    }
+
+    pub fn is_done(&self, time: f64) -> bool {
+        time >= self.duration as f64
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Dig {
+    rows: usize,
+    /// Garbage rows put on the field so far
+    inserted: usize,
+    cleared: usize,
+}
+
+impl Dig {
+    fn new(rows: usize) -> Dig {
+        Dig {
+            rows,
+            inserted: 0,
+            cleared: 0,
+        }
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Garbage rows left to clear, including those not on the field yet
+    pub fn remaining(&self) -> usize {
+        self.rows - self.cleared
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.cleared >= self.rows
+    }
+
+    pub fn cleared_garbage(&mut self, rows: usize) {
+        self.cleared = (self.cleared + rows).min(self.inserted);
+    }
+
+    /// Counts the rows that should be added to fill the field up again
+    pub fn refill(&mut self) -> usize {
+        let on_field = self.inserted - self.cleared;
+        let rows = DIG_VISIBLE_ROWS
+            .saturating_sub(on_field)
+            .min(self.rows - self.inserted);
+        self.inserted += rows;
+        rows
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct PuzzleProgress {
+    goal: PuzzleGoal,
+    lines: usize,
+    solved: bool,
+}
+
+impl PuzzleProgress {
+    fn new(goal: PuzzleGoal) -> PuzzleProgress {
+        PuzzleProgress {
+            goal,
+            lines: 0,
+            solved: false,
+        }
+    }
+
+    pub fn goal(&self) -> PuzzleGoal {
+        self.goal
+    }
+
+    pub fn lines(&self) -> usize {
+        self.lines
+    }
+
+    pub fn is_solved(&self) -> bool {
+        self.solved
+    }
+
+    pub fn cleared_lines(&mut self, clear: LineClear) {
+        self.lines += clear.lines;
+        self.solved |= match self.goal {
+            PuzzleGoal::Lines(lines) => self.lines >= lines,
+            PuzzleGoal::PerfectClear => clear.perfect_clear,
+            PuzzleGoal::TSpinTriple => clear.spin == Spin::Full && clear.lines == 3,
+        };
+    }
+}
+
+/// Rows fallen per frame at 60 frames a second (G) on the guideline levels, following
+/// `(0.8 - (level - 1) * 0.007)^(level - 1)` seconds per row until it reaches 20G
+const GUIDELINE_GRAVITY: [f64; 19] = [
+    0.01667,
+    0.02102,
+    0.02698,
+    0.03526,
+    0.04692,
+    0.06361,
+    0.08787,
+    0.1237,
+    0.17753,
+    0.2598,
+    0.38781,
+    0.59065,
+    0.91811,
+    1.45696,
+    2.36118,
+    3.9091,
+    6.61354,
+    11.43794,
+    GRAVITY_20G,
+];
+/// Lines to clear on a guideline level to reach the next one
+const GUIDELINE_LEVEL_LINES: usize = 10;
+/// Levels of a guideline marathon
+const MARATHON_LEVELS: usize = 15;
+/// Frames per row of the levels online games go through
+const CLASSIC_FRAMES: [f64; 12] = [60., 50., 40., 30., 20., 10., 8., 6., 4., 2., 1., 0.3];
+const CLASSIC_LEVEL_LINES: usize = 5;
+/// Gravity that puts a piece on the floor in the frame it appears
+pub const GRAVITY_20G: f64 = 20.;
+const FRAMES_PER_SECOND: f64 = 60.;
+
+/// Turns gravity in G into the seconds a piece takes to fall a row
+pub fn seconds_per_row(gravity: f64) -> f64 {
+    1. / (gravity * FRAMES_PER_SECOND)
+}
+
+/// The delays around a piece locking, in seconds
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Timings {
+    /// From a lock to the next piece (ARE)
+    pub are: f64,
+    /// From a lock that cleared lines to the next piece, after the clear delay
+    pub line_are: f64,
+    /// From a lock that cleared lines until the rows above fall
+    pub line_clear: f64,
+    /// How long a piece can rest on the ground
+    pub lock: f64,
+}
+
+impl Timings {
+    pub fn default() -> Timings {
+        Timings {
+            are: 0.11,
+            line_are: 0.44,
+            line_clear: 0.3,
+            lock: 0.5,
+        }
+    }
+
+    fn from_frames(frames: [u32; 4]) -> Timings {
+        let [are, line_are, line_clear, lock] = frames;
+        let seconds = |frames: u32| frames as f64 / FRAMES_PER_SECOND;
+        Timings {
+            are: seconds(are),
+            line_are: seconds(line_are),
+            line_clear: seconds(line_clear),
+            lock: seconds(lock),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LevelSpec {
+    /// In G, rows per frame
+    pub gravity: f64,
+    /// Lines to clear on this level to reach the next
+    pub lines: usize,
+}
+
+/// The levels of a game, from level 1 up to the cap
+#[derive(Clone, Debug)]
+pub struct LevelTable {
+    levels: Vec<LevelSpec>,
+    /// Whether clearing the lines of the last level finishes the game
+    finishes: bool,
+}
+
+impl LevelTable {
+    /// The 15 guideline levels, finished after 150 lines
+    pub fn marathon() -> LevelTable {
+        LevelTable {
+            levels: Self::guideline_levels(MARATHON_LEVELS),
+            finishes: true,
+        }
+    }
+
+    /// The guideline levels going on up to 20G, which is kept forever
+    pub fn endless() -> LevelTable {
+        LevelTable {
+            levels: Self::guideline_levels(GUIDELINE_GRAVITY.len()),
+            finishes: false,
+        }
+    }
+
+    /// The levels online games speed up through
+    pub fn classic() -> LevelTable {
+        let levels = CLASSIC_FRAMES
+            .iter()
+            .map(|frames| LevelSpec {
+                gravity: 1. / frames,
+                lines: CLASSIC_LEVEL_LINES,
+            })
+            .collect();
+        LevelTable {
+            levels,
+            finishes: false,
+        }
+    }
+
+    fn guideline_levels(count: usize) -> Vec<LevelSpec> {
+        GUIDELINE_GRAVITY[..count]
+            .iter()
+            .map(|&gravity| LevelSpec {
+                gravity,
+                lines: GUIDELINE_LEVEL_LINES,
+            })
+            .collect()
+    }
+
+    /// The highest level
+    pub fn cap(&self) -> usize {
+        self.levels.len()
+    }
+
+    /// Levels start at 1, those past the cap play like the cap
+    pub fn spec(&self, level: usize) -> LevelSpec {
+        self.levels[level.max(1).min(self.cap()) - 1]
+    }
+
+    /// Seconds a piece takes to fall a row on the level
+    pub fn gravity(&self, level: usize) -> f64 {
+        seconds_per_row(self.spec(level).gravity)
+    }
+
+    /// The level reached after clearing this many lines in total
+    pub fn level_for_lines(&self, lines: usize) -> usize {
+        let mut needed = 0;
+        for (i, spec) in self.levels.iter().enumerate() {
+            needed += spec.lines;
+            if lines < needed {
+                return i + 1;
+            }
+        }
+        self.cap()
+    }
+
+    /// Lines that finish the game, if it can be finished
+    pub fn goal(&self) -> Option<usize> {
+        if self.finishes {
+            Some(self.levels.iter().map(|spec| spec.lines).sum())
+        } else {
+            None
+        }
+    }
+}
+
+/// Master mode: every piece and line raise the level, the game ends at 999
+/// and the score decides the grade.
+#[derive(Clone, Debug)]
+pub struct Master {
+    level: usize,
+    score: usize,
+    /// Grows with consecutive clears, 1 without any
+    combo: usize,
+    /// Whether the piece that is falling or just locked cleared lines
+    cleared: bool,
+    /// Cells the current piece was soft dropped by
+    soft_dropped: usize,
+    /// Time at the end of every section
+    sections: Vec<f64>,
+    /// Whether every check for the grand master grade so far was passed
+    grand_master: bool,
+}
+
+impl Master {
+    fn new() -> Master {
+        Master {
+            level: 0,
+            score: 0,
+            combo: 1,
+            cleared: false,
+            soft_dropped: 0,
+            sections: vec![],
+            grand_master: true,
+        }
+    }
+
+    pub fn level(&self) -> usize {
+        self.level
+    }
+
+    pub fn score(&self) -> usize {
+        self.score
+    }
+
+    pub fn sections(&self) -> &[f64] {
+        &self.sections
+    }
+
+    /// The level the current section ends at, which pieces alone can't pass
+    pub fn section_end(&self) -> usize {
+        ((self.level / MASTER_SECTION_LEVELS + 1) * MASTER_SECTION_LEVELS).min(MASTER_LEVEL_MAX)
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.level >= MASTER_LEVEL_MAX
+    }
+
+    pub fn grade(&self) -> String {
+        if self.is_done() && self.grand_master {
+            return "GM".to_string();
+        }
+        MASTER_GRADES
+            .iter()
+            .rev()
+            .find(|(_, score)| self.score >= *score)
+            .map_or("9", |(grade, _)| grade)
+            .to_string()
+    }
+
+    fn gravity(&self) -> f64 {
+        let (_, gravity) = Self::at_level(&MASTER_GRAVITY, self.level);
+        seconds_per_row(gravity as f64 / 256.)
+    }
+
+    fn timings(&self) -> Timings {
+        Timings::from_frames(Self::at_level(&MASTER_TIMINGS, self.level).1)
+    }
+
+    /// The last entry of a table that starts at or below the current level
+    fn at_level<T: Copy>(table: &[(usize, T)], level: usize) -> (usize, T) {
+        table
+            .iter()
+            .rev()
+            .find(|(from, _)| *from <= level)
+            .copied()
+            .unwrap_or(table[0])
+    }
+
+    fn piece_spawned(&mut self) {
+        if !self.cleared {
+            self.combo = 1;
+        }
+        self.cleared = false;
+        self.soft_dropped = 0;
+        if self.level + 1 < self.section_end() {
+            self.level += 1;
+        }
+    }
+
+    fn dropped(&mut self, cells: usize, hard: bool) {
+        if !hard {
+            self.soft_dropped += cells;
+        }
+    }
+
+    /// Scores the clear and raises the level by its lines, `time` is the game time it happened at
+    pub fn cleared_lines(&mut self, clear: LineClear, time: f64) {
+        if clear.lines == 0 {
+            return;
+        }
+        self.cleared = true;
+        self.combo += 2 * clear.lines - 2;
+        let bravo = if clear.perfect_clear { 4 } else { 1 };
+        let base = (self.level + clear.lines + 3) / 4 + self.soft_dropped;
+        self.score += base * clear.lines * self.combo * bravo;
+
+        let before = self.level;
+        self.level = (self.level + clear.lines).min(MASTER_LEVEL_MAX);
+        for _ in before / MASTER_SECTION_LEVELS..self.level / MASTER_SECTION_LEVELS {
+            self.sections.push(time);
+        }
+        if self.is_done() && self.sections.len() < MASTER_LEVEL_MAX / MASTER_SECTION_LEVELS + 1 {
+            self.sections.push(time);
+        }
+        for (level, score, limit) in MASTER_GM_CHECKS.iter() {
+            if before < *level && self.level >= *level {
+                self.grand_master &= self.score >= *score && time <= *limit;
+            }
+        }
+    }
+}
@@ -0,0 +1,18 @@
+pub mod attack;
+mod config;
+mod engine;
+mod handling;
+mod input;
+pub mod level;
+mod position;
+mod randomizer;
+mod replay;
+pub mod scoring;
+
+pub use config::*;
+pub use engine::*;
+pub use handling::*;
+pub use input::*;
+pub use position::*;
+pub use randomizer::*;
+pub use replay::*;
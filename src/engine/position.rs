@@ -0,0 +1,89 @@
+use crate::field::{BoardSize, PlayingField};
+use crate::piece::{PieceSet, PieceShape};
+use serde::{Deserialize, Serialize};
+
+/// A board to start a game from instead of an empty one, with the pieces to come first.
+///
+/// In text it is written as `key: value` lines followed by the rows of the field,
+/// where `size` can be left out for the usual 10x20:
+/// ```text
+/// size: 10x20
+/// queue: TIO
+/// hold: L
+/// ###...####
+/// ####.#####
+/// ```
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Position {
+    pub field: PlayingField,
+    /// Pieces that come before the randomizer takes over
+    pub queue: Vec<PieceShape>,
+    pub hold: Option<PieceShape>,
+}
+
+impl Position {
+    pub fn new() -> Position {
+        Position {
+            field: PlayingField::new(),
+            queue: vec![],
+            hold: None,
+        }
+    }
+
+    pub fn from_text(text: &str) -> Result<Position, String> {
+        let mut position = Position::new();
+        let mut size = BoardSize::default();
+        let mut rows = vec![];
+        for line in text.lines().map(str::trim).filter(|l| !l.is_empty()) {
+            if let Some(i) = line.find(':') {
+                let (key, value) = (line[..i].trim(), line[i + 1..].trim());
+                match key {
+                    "size" => size = BoardSize::from_text(value)?,
+                    "queue" => position.queue = parse_shapes(value)?,
+                    "hold" => {
+                        let mut shapes = parse_shapes(value)?;
+                        if shapes.len() > 1 {
+                            return Err("Only one piece can be held".to_string());
+                        }
+                        position.hold = shapes.pop();
+                    }
+                    _ => return Err(format!("Unknown key '{}'", key)),
+                }
+            } else {
+                rows.push(line);
+            }
+        }
+        position.field = PlayingField::from_rows(&rows, size)?;
+        Ok(position)
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        let size = self.field.size();
+        if size != BoardSize::default() {
+            text += &format!("size: {}\n", size.name());
+        }
+        text += &format!("queue: {}\n", shapes_text(&self.queue));
+        if let Some(hold) = &self.hold {
+            text += &format!("hold: {}\n", hold.letter());
+        }
+        for row in self.field.to_rows() {
+            text += &row;
+            text.push('\n');
+        }
+        text
+    }
+}
+
+/// Reads letters of the standard pieces, ignoring spaces
+pub fn parse_shapes(text: &str) -> Result<Vec<PieceShape>, String> {
+    let set = PieceSet::standard();
+    text.chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| set.shape(c).ok_or_else(|| format!("Unknown piece '{}'", c)))
+        .collect()
+}
+
+pub fn shapes_text(shapes: &[PieceShape]) -> String {
+    shapes.iter().map(PieceShape::letter).collect()
+}
@@ -0,0 +1,288 @@
+use crate::piece::PieceShape;
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fmt::Debug;
+
+/// Produces the sequence of pieces for a game. The same seed always gives the same sequence.
+pub trait PieceGenerator: Debug {
+    fn next_shape(&mut self) -> PieceShape;
+    fn seed(&self) -> u64;
+    fn box_clone(&self) -> Box<dyn PieceGenerator>;
+}
+
+impl Clone for Box<dyn PieceGenerator> {
+    fn clone(&self) -> Self {
+        self.box_clone()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Randomizer {
+    /// Every piece once per bag, a bag of 7 for the tetrominoes
+    Bag7,
+    /// Every piece twice per bag
+    Bag14,
+    /// Every piece has the same chance, no memory
+    Random,
+    /// TGM style: rerolls pieces that are among the last 4
+    History4,
+}
+
+impl Randomizer {
+    pub fn all() -> Vec<Randomizer> {
+        use Randomizer::*;
+        vec![Bag7, Bag14, Random, History4]
+    }
+
+    pub fn name(self) -> &'static str {
+        use Randomizer::*;
+        match self {
+            Bag7 => "Bag",
+            Bag14 => "Double Bag",
+            Random => "Random",
+            History4 => "History",
+        }
+    }
+
+    /// Returns a generator that deals out the given pieces
+    pub fn generator(self, seed: u64, shapes: Vec<PieceShape>) -> Box<dyn PieceGenerator> {
+        use Randomizer::*;
+        match self {
+            Bag7 => Box::new(BagGenerator::new(seed, shapes, 1)),
+            Bag14 => Box::new(BagGenerator::new(seed, shapes, 2)),
+            Random => Box::new(RandomGenerator::new(seed, shapes)),
+            History4 => Box::new(HistoryGenerator::new(seed, shapes, HISTORY_ROLLS)),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct BagGenerator {
+    seed: u64,
+    rng: StdRng,
+    shapes: Vec<PieceShape>,
+    copies: usize,
+    bag: Vec<PieceShape>,
+}
+
+impl BagGenerator {
+    pub fn new(seed: u64, shapes: Vec<PieceShape>, copies: usize) -> Self {
+        Self {
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+            shapes,
+            copies,
+            bag: vec![],
+        }
+    }
+}
+
+impl PieceGenerator for BagGenerator {
+    fn next_shape(&mut self) -> PieceShape {
+        if self.bag.is_empty() {
+            for _ in 0..self.copies {
+                self.bag.extend(self.shapes.iter().cloned());
+            }
+            self.bag.shuffle(&mut self.rng);
+        }
+        self.bag.remove(0)
+    }
+    fn seed(&self) -> u64 {
+        self.seed
+    }
+    fn box_clone(&self) -> Box<dyn PieceGenerator> {
+        Box::new(self.clone())
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct RandomGenerator {
+    seed: u64,
+    rng: StdRng,
+    shapes: Vec<PieceShape>,
+}
+
+impl RandomGenerator {
+    pub fn new(seed: u64, shapes: Vec<PieceShape>) -> Self {
+        Self {
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+            shapes,
+        }
+    }
+}
+
+impl PieceGenerator for RandomGenerator {
+    fn next_shape(&mut self) -> PieceShape {
+        random_shape(&mut self.rng, &self.shapes)
+    }
+    fn seed(&self) -> u64 {
+        self.seed
+    }
+    fn box_clone(&self) -> Box<dyn PieceGenerator> {
+        Box::new(self.clone())
+    }
+}
+
+const HISTORY_LEN: usize = 4;
+const HISTORY_ROLLS: usize = 6;
+/// The history starts out as these pieces, if the set has them
+const HISTORY_START: [char; 4] = ['Z', 'S', 'S', 'Z'];
+/// Pieces that can't be placed first without a hole
+const HISTORY_NOT_FIRST: [char; 3] = ['S', 'Z', 'O'];
+
+#[derive(Clone, Debug)]
+pub struct HistoryGenerator {
+    seed: u64,
+    rng: StdRng,
+    shapes: Vec<PieceShape>,
+    rolls: usize,
+    history: VecDeque<PieceShape>,
+    first: bool,
+}
+
+impl HistoryGenerator {
+    pub fn new(seed: u64, shapes: Vec<PieceShape>, rolls: usize) -> Self {
+        let history = HISTORY_START
+            .iter()
+            .filter_map(|letter| shapes.iter().find(|s| s.letter() == *letter).cloned())
+            .collect();
+        Self {
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+            shapes,
+            rolls,
+            history,
+            first: true,
+        }
+    }
+}
+
+impl PieceGenerator for HistoryGenerator {
+    fn next_shape(&mut self) -> PieceShape {
+        let shape = if self.first {
+            self.first = false;
+            let firsts: Vec<&PieceShape> = self
+                .shapes
+                .iter()
+                .filter(|s| !HISTORY_NOT_FIRST.contains(&s.letter()))
+                .collect();
+            match firsts.choose(&mut self.rng) {
+                Some(shape) => (*shape).clone(),
+                None => random_shape(&mut self.rng, &self.shapes),
+            }
+        } else {
+            let mut shape = random_shape(&mut self.rng, &self.shapes);
+            for _ in 1..self.rolls {
+                if !self.history.contains(&shape) {
+                    break;
+                }
+                shape = random_shape(&mut self.rng, &self.shapes);
+            }
+            shape
+        };
+        self.history.push_back(shape.clone());
+        if self.history.len() > HISTORY_LEN {
+            self.history.pop_front();
+        }
+        shape
+    }
+    fn seed(&self) -> u64 {
+        self.seed
+    }
+    fn box_clone(&self) -> Box<dyn PieceGenerator> {
+        Box::new(self.clone())
+    }
+}
+
+/// Hands out a fixed list of pieces first and then continues with another generator
+#[derive(Clone, Debug)]
+pub struct FixedGenerator {
+    queue: VecDeque<PieceShape>,
+    then: Box<dyn PieceGenerator>,
+}
+
+impl FixedGenerator {
+    pub fn new(queue: Vec<PieceShape>, then: Box<dyn PieceGenerator>) -> Self {
+        Self {
+            queue: queue.into_iter().collect(),
+            then,
+        }
+    }
+}
+
+impl PieceGenerator for FixedGenerator {
+    fn next_shape(&mut self) -> PieceShape {
+        self.queue
+            .pop_front()
+            .unwrap_or_else(|| self.then.next_shape())
+    }
+    fn seed(&self) -> u64 {
+        self.then.seed()
+    }
+    fn box_clone(&self) -> Box<dyn PieceGenerator> {
+        Box::new(self.clone())
+    }
+}
+
+fn random_shape(rng: &mut StdRng, shapes: &[PieceShape]) -> PieceShape {
+    shapes[rng.gen_range(0, shapes.len())].clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::piece::PieceSet;
+
+    fn letters(randomizer: Randomizer, seed: u64, count: usize) -> Vec<char> {
+        let mut generator = randomizer.generator(seed, PieceSet::standard().shapes);
+        (0..count)
+            .map(|_| generator.next_shape().letter())
+            .collect()
+    }
+
+    fn sorted(letters: &[char]) -> Vec<char> {
+        let mut letters = letters.to_vec();
+        letters.sort();
+        letters
+    }
+
+    #[test]
+    fn same_seed_same_pieces() {
+        for randomizer in Randomizer::all() {
+            assert_eq!(letters(randomizer, 42, 100), letters(randomizer, 42, 100));
+        }
+    }
+
+    #[test]
+    fn bags_deal_every_piece_per_bag() {
+        let set = sorted(
+            &PieceSet::standard()
+                .shapes
+                .iter()
+                .map(|s| s.letter())
+                .collect::<Vec<_>>(),
+        );
+        for bag in letters(Randomizer::Bag7, 7, 7 * 20).chunks(7) {
+            assert_eq!(sorted(bag), set);
+        }
+        let twice = sorted(&[set.clone(), set].concat());
+        for bag in letters(Randomizer::Bag14, 14, 14 * 20).chunks(14) {
+            assert_eq!(sorted(bag), twice);
+        }
+    }
+
+    #[test]
+    fn history_never_starts_with_s_z_or_o() {
+        for seed in 0..200 {
+            let first = letters(Randomizer::History4, seed, 1)[0];
+            assert!(
+                !HISTORY_NOT_FIRST.contains(&first),
+                "seed {} starts with {}",
+                seed,
+                first
+            );
+        }
+    }
+}
@@ -0,0 +1,180 @@
+use super::level::LevelingType;
+use super::{Engine, EngineConfig, Handling, Input};
+use bincode::Options;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Bumped whenever the engine changes in a way that makes old replays play out differently
+const REPLAY_VERSION: u32 = 10;
+const REPLAY_EXTENSION: &str = "replay";
+/// Far more than hours of play, but a broken file can't make the game allocate without end
+const REPLAY_LEN_MAX: u64 = 16 * 1024 * 1024;
+
+/// Something that was put into the engine from outside
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ReplayEvent {
+    Input(Input),
+    /// Garbage sent by the opponent
+    Garbage(usize),
+}
+
+/// A recorded game. The engine is deterministic, so the config with its seed and
+/// the inputs, each stamped with the tick it happened before, are enough to play it again.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Replay {
+    version: u32,
+    pub config: EngineConfig,
+    pub online: bool,
+    /// The handling the player used, only for reference
+    pub handling: Handling,
+    pub events: Vec<(u64, ReplayEvent)>,
+    /// Length of the game in ticks
+    pub ticks: u64,
+}
+
+impl Replay {
+    pub fn new(engine: &Engine, online: bool, handling: Handling) -> Replay {
+        Replay {
+            version: REPLAY_VERSION,
+            config: engine.config().clone(),
+            online,
+            handling,
+            events: engine.recording().to_vec(),
+            ticks: engine.ticks(),
+        }
+    }
+
+    /// A fresh engine in the state the recorded game started in
+    pub fn engine(&self) -> Engine {
+        Engine::new(
+            LevelingType::new(self.online, self.config.mode),
+            self.config.clone(),
+        )
+    }
+
+    pub fn apply(engine: &mut Engine, event: ReplayEvent) {
+        match event {
+            ReplayEvent::Input(input) => {
+                engine.input(input);
+            }
+            ReplayEvent::Garbage(amount) => engine.add_garbage_lines(amount),
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Replay, String> {
+        let bytes = fs::read(path).map_err(|e| e.to_string())?;
+        let replay: Replay = options().deserialize(&bytes).map_err(|e| e.to_string())?;
+        if replay.version != REPLAY_VERSION {
+            return Err(format!(
+                "Replay is from version {}, this game plays version {}",
+                replay.version, REPLAY_VERSION
+            ));
+        }
+        replay.config.board.check()?;
+        if let Some(position) = &replay.config.position {
+            position.field.size().check()?;
+        }
+        Ok(replay)
+    }
+
+    /// Saves the replay into the replay directory, named by the current time
+    pub fn save(&self) -> Result<PathBuf, String> {
+        let dir = replay_dir()?;
+        fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+        let millis = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(|e| e.to_string())?
+            .as_millis();
+        let path = dir.join(format!("{}.{}", millis, REPLAY_EXTENSION));
+        let bytes = options().serialize(self).map_err(|e| e.to_string())?;
+        fs::write(&path, bytes).map_err(|e| e.to_string())?;
+        Ok(path)
+    }
+
+    /// Lists the saved replays, newest first
+    pub fn list() -> Result<Vec<PathBuf>, String> {
+        let dir = replay_dir()?;
+        if !dir.exists() {
+            return Ok(vec![]);
+        }
+        let mut paths: Vec<PathBuf> = fs::read_dir(dir)
+            .map_err(|e| e.to_string())?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().map_or(false, |e| e == REPLAY_EXTENSION))
+            .collect();
+        paths.sort();
+        paths.reverse();
+        Ok(paths)
+    }
+}
+
+/// Variable length integers keep the tick stamps small
+fn options() -> impl Options {
+    bincode::DefaultOptions::new().with_limit(REPLAY_LEN_MAX)
+}
+
+fn replay_dir() -> Result<PathBuf, String> {
+    dirs::data_dir()
+        .map(|dir| dir.join("tetro-98").join("replays"))
+        .ok_or_else(|| "No data directory found".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::level::GameMode;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    fn score(engine: &Engine) -> usize {
+        match engine.leveling() {
+            LevelingType::Single(single) => single.get_score(),
+            leveling => panic!("not a marathon: {:?}", leveling),
+        }
+    }
+
+    #[test]
+    fn replay_plays_out_the_same() {
+        use Input::*;
+        let inputs = [
+            MoveLeft, MoveRight, SoftDrop, HardDrop, RotateCW, RotateCCW, Rotate180, Hold,
+        ];
+        let mut config = EngineConfig::new();
+        config.mode = GameMode::Marathon;
+        config.seed = 7;
+        let mut engine = Engine::new(LevelingType::new(false, config.mode), config);
+        let mut rng = StdRng::seed_from_u64(7);
+        while engine.ticks() < 60 * 60 && !engine.is_game_over() {
+            if rng.gen_bool(0.3) {
+                engine.input(inputs[rng.gen_range(0, inputs.len())]);
+            }
+            if rng.gen_bool(0.002) {
+                engine.add_garbage_lines(rng.gen_range(1, 4));
+            }
+            engine.tick();
+        }
+        assert!(engine.pieces() > 10 && score(&engine) > 0);
+
+        let bytes = options()
+            .serialize(&Replay::new(&engine, false, Handling::default()))
+            .unwrap();
+        let replay: Replay = options().deserialize(&bytes).unwrap();
+        let mut replayed = replay.engine();
+        let mut events = replay.events.iter().peekable();
+        loop {
+            while let Some((_, event)) = events.next_if(|(tick, _)| *tick <= replayed.ticks()) {
+                Replay::apply(&mut replayed, *event);
+            }
+            if replayed.ticks() >= replay.ticks || replayed.is_game_over() {
+                break;
+            }
+            replayed.tick();
+        }
+
+        assert_eq!(replayed.ticks(), engine.ticks());
+        assert_eq!(replayed.pieces(), engine.pieces());
+        assert_eq!(score(&replayed), score(&engine));
+        assert_eq!(replayed.field().to_rows(), engine.field().to_rows());
+    }
+}
@@ -0,0 +1,156 @@
+/// How the piece that caused a clear got into place
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Spin {
+    None,
+    /// T-spin where only one of the corners in front of the T is filled
+    Mini,
+    Full,
+}
+
+/// The result of locking a piece that cleared lines or was a spin
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LineClear {
+    pub lines: usize,
+    pub spin: Spin,
+    /// Consecutive clearing locks before this one, 0 for the first
+    pub combo: usize,
+    /// Whether this and the previous difficult clear followed each other
+    pub back_to_back: bool,
+    pub perfect_clear: bool,
+}
+
+impl LineClear {
+    /// Returns whether this clear keeps back-to-back going: a tetris or any spin clearing lines
+    pub fn is_difficult(&self) -> bool {
+        self.lines >= 4 || (self.spin != Spin::None && self.lines > 0)
+    }
+
+    /// Returns the guideline score of this clear without any bonuses at level 1
+    pub fn base_score(&self) -> usize {
+        match (self.spin, self.lines) {
+            (Spin::None, 1) => 100,
+            (Spin::None, 2) => 300,
+            (Spin::None, 3) => 500,
+            (Spin::None, 4) => 800,
+            (Spin::Mini, 0) => 100,
+            (Spin::Mini, 1) => 200,
+            (Spin::Mini, _) => 400,
+            (Spin::Full, 0) => 400,
+            (Spin::Full, 1) => 800,
+            (Spin::Full, 2) => 1200,
+            (Spin::Full, _) => 1600,
+            _ => 0,
+        }
+    }
+
+    /// Returns the score of this clear with back-to-back, combo and perfect clear bonuses
+    pub fn score(&self, level: usize) -> usize {
+        let mut score = self.base_score();
+        if self.back_to_back {
+            score += score / 2;
+        }
+        if self.lines > 0 {
+            score += 50 * self.combo;
+        }
+        if self.perfect_clear {
+            score += match (self.lines, self.back_to_back) {
+                (1, _) => 800,
+                (2, _) => 1200,
+                (3, _) => 1800,
+                (_, false) => 2000,
+                (_, true) => 3200,
+            };
+        }
+        score * level
+    }
+
+    /// Returns the text shown on screen for this clear, if it's worth mentioning
+    pub fn callout(&self) -> Option<String> {
+        let lines = match self.lines {
+            0 => "",
+            1 => "SINGLE",
+            2 => "DOUBLE",
+            3 => "TRIPLE",
+            _ => "TETRIS",
+        };
+        let mut callout: Vec<String> = vec![];
+        match self.spin {
+            Spin::None if self.lines >= 4 => callout.push(lines.to_string()),
+            Spin::None => {}
+            Spin::Mini => callout.push(format!("T-SPIN MINI {}", lines)),
+            Spin::Full => callout.push(format!("T-SPIN {}", lines)),
+        }
+        if self.back_to_back {
+            if let Some(first) = callout.first_mut() {
+                *first = format!("B2B {}", first);
+            }
+        }
+        if self.combo > 0 && self.lines > 0 {
+            callout.push(format!("{} COMBO", self.combo));
+        }
+        if self.perfect_clear {
+            callout.push("PERFECT CLEAR".to_string());
+        }
+        if callout.is_empty() {
+            None
+        } else {
+            Some(
+                callout
+                    .iter()
+                    .map(|s| s.trim_end())
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clear(lines: usize, spin: Spin) -> LineClear {
+        LineClear {
+            lines,
+            spin,
+            combo: 0,
+            back_to_back: false,
+            perfect_clear: false,
+        }
+    }
+
+    #[test]
+    fn bonuses() {
+        let tetris = clear(4, Spin::None);
+        let tsd = clear(2, Spin::Full);
+        let double = clear(2, Spin::None);
+        #[rustfmt::skip]
+        let table = [
+            (clear(1, Spin::None), 1, 100),
+            (double, 3, 900),
+            // back-to-back: ×1.5
+            (LineClear { back_to_back: true, ..tetris }, 1, 1200),
+            (LineClear { back_to_back: true, ..tsd }, 1, 1800),
+            (LineClear { back_to_back: true, ..tsd }, 2, 3600),
+            // combo: 50 for every clear before this one, only when lines are cleared
+            (LineClear { combo: 1, ..double }, 1, 350),
+            (LineClear { combo: 3, ..double }, 1, 450),
+            (LineClear { combo: 3, ..clear(0, Spin::Mini) }, 1, 100),
+            // perfect clear: by lines, more for a back-to-back tetris
+            (LineClear { perfect_clear: true, ..clear(1, Spin::None) }, 1, 900),
+            (LineClear { perfect_clear: true, ..double }, 1, 1500),
+            (LineClear { perfect_clear: true, ..clear(3, Spin::None) }, 1, 2300),
+            (LineClear { perfect_clear: true, ..tetris }, 1, 2800),
+            (LineClear { perfect_clear: true, back_to_back: true, ..tetris }, 1, 4400),
+        ];
+        for (clear, level, score) in table.iter() {
+            assert_eq!(
+                clear.score(*level),
+                *score,
+                "{:?} at level {}",
+                clear,
+                level
+            );
+        }
+    }
+}
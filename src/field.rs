@@ -1,32 +1,90 @@
-use crate::block::{Block, BlockColor, BLOCK_SIZE};
-use crate::get_win_dim;
+use crate::block::{Block, BlockColor};
 use crate::piece::Piece;
-use crate::util::{colors, Align};
-use ggez::graphics::*;
-use ggez::{Context, GameResult};
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
 use std::convert::TryInto;
 
+/// Size of the field unless a game chooses another
 pub const FIELD_WIDTH: usize = 10;
-pub const FIELD_HEIGHT: usize = 32;
 pub const FIELD_HEIGHT_VIS: usize = 20;
+/// Rows above the visible ones, where pieces spawn
+const FIELD_HIDDEN_ROWS: usize = 12;
+/// Narrower fields can't fit every piece
+pub const FIELD_WIDTH_MIN: usize = 4;
+/// Lower fields can't fit the grid of the largest pieces when they spawn
+pub const FIELD_HEIGHT_MIN: usize = 5;
+/// Columns and visible rows at most, also the most a board sent over the network has
+pub const FIELD_SIDE_MAX: usize = 64;
 
-pub const FIELD_WIDTH_REAL: f32 = FIELD_WIDTH as f32 * BLOCK_SIZE;
-pub const FIELD_HEIGHT_REAL: f32 = FIELD_HEIGHT as f32 * BLOCK_SIZE;
-pub const FIELD_HEIGHT_VIS_REAL: f32 = FIELD_HEIGHT_VIS as f32 * BLOCK_SIZE;
+pub type Row = Vec<Option<Block>>;
 
-pub const FIELD_OFF: (f32, f32) = (BLOCK_SIZE * 4., BLOCK_SIZE);
-pub const FIELD_ALIGN: Align = Align::CC(0., 0.);
-// pub const FIELD_ALIGN: Align = Align::TL(FIELself.current_source.try_borrow_mut().unwrap().D_OFF.0, FIELD_OFF.1);
+/// Columns and visible rows of a field
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BoardSize {
+    pub width: usize,
+    pub height: usize,
+}
+
+impl BoardSize {
+    pub fn default() -> BoardSize {
+        BoardSize {
+            width: FIELD_WIDTH,
+            height: FIELD_HEIGHT_VIS,
+        }
+    }
+
+    /// The sizes to choose from in the menu
+    pub fn all() -> Vec<BoardSize> {
+        [(10, 20), (4, 20), (6, 20), (20, 20), (10, 40)]
+            .iter()
+            .map(|&(width, height)| BoardSize { width, height })
+            .collect()
+    }
+
+    pub fn name(self) -> String {
+        format!("{}x{}", self.width, self.height)
+    }
+
+    /// Reads `<width>x<height>`
+    pub fn from_text(text: &str) -> Result<BoardSize, String> {
+        let invalid = || format!("'{}' is not a board size like 10x20", text);
+        let mut parts = text.trim().splitn(2, 'x');
+        let mut number = || {
+            parts
+                .next()
+                .and_then(|n| n.trim().parse::<usize>().ok())
+                .ok_or_else(invalid)
+        };
+        let size = BoardSize {
+            width: number()?,
+            height: number()?,
+        };
+        size.check().map(|_| size)
+    }
 
-const GRID_WIDTH: f32 = 1.;
+    /// Rejects sizes the game can't be played on
+    pub fn check(self) -> Result<(), String> {
+        if self.width < FIELD_WIDTH_MIN || self.height < FIELD_HEIGHT_MIN {
+            return Err(format!(
+                "A board needs at least {} columns and {} rows",
+                FIELD_WIDTH_MIN, FIELD_HEIGHT_MIN
+            ));
+        }
+        if self.width > FIELD_SIDE_MAX || self.height > FIELD_SIDE_MAX {
+            return Err(format!(
+                "A board has at most {} columns and {} rows",
+                FIELD_SIDE_MAX, FIELD_SIDE_MAX
+            ));
+        }
+        Ok(())
+    }
+}
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct PlayingField {
-    field: [[Option<Block>; FIELD_WIDTH]; FIELD_HEIGHT],
-    meshes: HashMap<BlockColor, Mesh>,
-    grid_line_mesh_v: Mesh,
-    grid_line_mesh_h: Mesh,
+    /// The hidden rows followed by the visible ones, top to bottom
+    field: Vec<Row>,
+    width: usize,
+    visible_height: usize,
 }
 
 #[derive(Clone, Debug, Copy, PartialEq)]
@@ -40,63 +98,52 @@ pub enum CollidingReason {
 }
 
 impl PlayingField {
-    pub fn new(ctx: &mut Context) -> PlayingField {
-        let grid_line_mesh_v = Mesh::new_rectangle(
-            ctx,
-            DrawMode::Fill(Default::default()),
-            Rect::new(
-                -GRID_WIDTH / 2.,
-                -GRID_WIDTH / 2.,
-                GRID_WIDTH,
-                FIELD_HEIGHT_VIS as f32 * BLOCK_SIZE,
-            ),
-            colors::BG_LINE_COLOR.into(),
-        )
-        .unwrap();
-        let grid_line_mesh_h = Mesh::new_rectangle(
-            ctx,
-            DrawMode::Fill(Default::default()),
-            Rect::new(
-                -GRID_WIDTH / 2.,
-                -GRID_WIDTH / 2.,
-                FIELD_WIDTH as f32 * BLOCK_SIZE,
-                GRID_WIDTH,
-            ),
-            colors::BG_LINE_COLOR.into(),
-        )
-        .unwrap();
+    pub fn new() -> PlayingField {
+        Self::with_size(BoardSize::default())
+    }
 
+    pub fn with_size(size: BoardSize) -> PlayingField {
         PlayingField {
-            field: [[None; FIELD_WIDTH]; FIELD_HEIGHT],
-            meshes: HashMap::new(),
-            grid_line_mesh_v,
-            grid_line_mesh_h,
+            field: vec![vec![None; size.width]; size.height + FIELD_HIDDEN_ROWS],
+            width: size.width,
+            visible_height: size.height,
+        }
+    }
+
+    pub fn size(&self) -> BoardSize {
+        BoardSize {
+            width: self.width,
+            height: self.visible_height,
         }
     }
 
-    // /// Gets cell with offset in TL corner translated to field offset
-    // pub fn get_cell(&self, x: usize, y: usize) -> Option<Block> {
-    //     self.field[y][x]
-    // }
+    pub fn empty_row(&self) -> Row {
+        vec![None; self.width]
+    }
+
+    /// Gets the cell at a field position, where y counts from the top of the hidden rows
+    pub fn get_cell(&self, x: usize, y: usize) -> Option<Block> {
+        self.field[y][x]
+    }
 
-    // pub fn set_cell(&mut self, x: usize, y: usize, item: Option<Block>) {
-    //     self.field[y][x] = item;
-    // }
+    pub fn set_cell(&mut self, x: usize, y: usize, item: Option<Block>) {
+        self.field[y][x] = item;
+    }
 
-    pub fn set_row(&mut self, y: usize, row: [Option<Block>; FIELD_WIDTH]) {
+    pub fn set_row(&mut self, y: usize, row: Row) {
         self.field[y] = row;
     }
 
     /// Freezes piece to field and returns whether should game_over
     /// Returns whether blocks overlap (should game over)
     pub fn add_piece(&mut self, piece: Piece) -> bool {
-        let (fields, _, block, _) = piece.deconstruct();
+        let (fields, _, block) = piece.deconstruct();
         let mut should_game_over = false;
         // self.meshes
         //     .insert(color, block.gen_mesh(ctx, -40).unwrap();
 
         for (x, y) in fields {
-            let y = if let Some(y) = PlayingField::adj_y(y) {
+            let y = if let Some(y) = self.adj_y(y) {
                 y
             } else {
                 panic!("field.add_piece: y < 0!");
@@ -106,7 +153,7 @@ impl PlayingField {
             }
             let x = x as usize;
             // let (x, y) = (x as usize, y as usize);
-            if y < FIELD_HEIGHT - FIELD_HEIGHT_VIS {
+            if y < FIELD_HIDDEN_ROWS {
                 should_game_over = true; // exceeding height
             }
 
@@ -121,21 +168,98 @@ impl PlayingField {
         should_game_over
     }
 
-    pub fn to_vec(&self) -> Vec<Vec<Option<Block>>> {
-        self.field.iter().map(|r| r.to_vec()).collect()
+    pub fn to_vec(&self) -> Vec<Row> {
+        self.field.clone()
+    }
+
+    /// Returns whether nothing is left on the field once the given rows are gone
+    pub fn perfect_clear(&self, cleared_rows: &[usize]) -> bool {
+        self.field
+            .iter()
+            .enumerate()
+            .all(|(y, row)| cleared_rows.contains(&y) || row.iter().all(|b| b.is_none()))
+    }
+
+    /// Returns the rows that are shown on screen, top to bottom
+    pub fn visible_rows(&self) -> &[Row] {
+        &self.field[FIELD_HIDDEN_ROWS..]
+    }
+
+    /// Index of the topmost visible row, where y counts from the top of the hidden rows
+    pub fn first_visible_row(&self) -> usize {
+        FIELD_HIDDEN_ROWS
     }
 
-    pub fn to_vec_shallow(&self) -> Vec<[Option<Block>; FIELD_WIDTH]> {
-        self.field.to_vec()
+    /// Builds a field from text rows, top to bottom, that sit on the floor.
+    /// Every row has one character per column: `.` for empty, `#` for garbage
+    /// or a piece letter for a block of that piece's color.
+    pub fn from_rows(rows: &[&str], size: BoardSize) -> Result<PlayingField, String> {
+        if rows.len() > size.height {
+            return Err(format!(
+                "The field has {} rows, at most {} fit",
+                rows.len(),
+                size.height
+            ));
+        }
+        let mut field = PlayingField::with_size(size);
+        let top = field.height() - rows.len();
+        for (i, text) in rows.iter().enumerate() {
+            let cells: Vec<char> = text.chars().collect();
+            if cells.len() != size.width {
+                return Err(format!(
+                    "Row '{}' has {} cells instead of {}",
+                    text,
+                    cells.len(),
+                    size.width
+                ));
+            }
+            for (x, cell) in cells.into_iter().enumerate() {
+                field.field[top + i][x] = match cell {
+                    '.' => None,
+                    _ => match BlockColor::from_letter(cell) {
+                        Some(color) => Some(Block::new(color)),
+                        None => return Err(format!("Unknown cell '{}' in row '{}'", cell, text)),
+                    },
+                };
+            }
+        }
+        Ok(field)
     }
+
+    /// Returns the rows from the highest one with blocks down to the floor,
+    /// in the format `from_rows` reads
+    pub fn to_rows(&self) -> Vec<String> {
+        self.field
+            .iter()
+            .skip_while(|row| row.iter().all(|b| b.is_none()))
+            .map(|row| {
+                row.iter()
+                    .map(|b| b.map_or('.', |b| b.block_color().letter()))
+                    .collect()
+            })
+            .collect()
+    }
+
     pub fn width(&self) -> usize {
-        self.field[0].len()
+        self.width
+    }
+
+    pub fn visible_height(&self) -> usize {
+        self.visible_height
     }
 
     pub fn height(&self) -> usize {
         self.field.len()
     }
 
+    /// Rows from the highest one with blocks down to the floor
+    pub fn stack_height(&self) -> usize {
+        self.field
+            .iter()
+            .position(|row| row.iter().any(Option::is_some))
+            .map_or(0, |top| self.height() - top)
+    }
+
     /*pub fn to_real(&self) -> Vec<(f32, f32, Block)> {
         let mut vec = vec![];
         for (y, row) in self
@@ -164,7 +288,7 @@ impl PlayingField {
     pub fn colliding_reason(&self, piece: &Piece) -> CollidingReason {
         let width = self.width();
         for (x, y) in piece.fields_tuple_offset(None) {
-            let field_y = if let Some(y) = Self::adj_y(y) {
+            let field_y = if let Some(y) = self.adj_y(y) {
                 y
             } else {
                 return CollidingReason::HitFloor;
@@ -175,7 +299,7 @@ impl PlayingField {
             let x = x as usize;
             if x + 1 > width {
                 return CollidingReason::LeaveRight;
-            } else if field_y >= FIELD_HEIGHT {
+            } else if field_y >= self.height() {
                 return CollidingReason::TopOut;
             } else if self.field[field_y][x].is_some() {
                 return CollidingReason::Overlap;
@@ -184,8 +308,21 @@ impl PlayingField {
         CollidingReason::None
     }
 
-    fn adj_y(y: isize) -> Option<usize> {
-        ((FIELD_HEIGHT - FIELD_HEIGHT_VIS) as isize + y) //- 1
+    /// Returns whether the cell is filled or lies beyond the walls or floor.
+    /// Cells above the top of the field count as free.
+    pub fn cell_blocked(&self, x: isize, y: isize) -> bool {
+        if x < 0 || x >= self.width() as isize {
+            return true;
+        }
+        match self.adj_y(y) {
+            Some(y) if y >= self.height() => true,
+            Some(y) => self.field[y][x as usize].is_some(),
+            None => false,
+        }
+    }
+
+    fn adj_y(&self, y: isize) -> Option<usize> {
+        (FIELD_HIDDEN_ROWS as isize + y) //- 1
             .try_into()
             .ok()
         // .unwrap_or(0)
@@ -199,11 +336,14 @@ impl PlayingField {
         self.colliding(piece)
     }
 
-    pub fn add_row(&mut self, y: usize, row: [Option<Block>; FIELD_WIDTH]) {
-        let mut field = self.to_vec_shallow();
-        field.insert(y, row);
-        field.remove(0);
-        self.field_from_vec_shallow(field);
+    /// Inserts the row before `y` and pushes everything above it up by one.
+    /// `y` equal to the height adds it at the bottom.
+    /// Returns whether that pushed blocks out of the top, which tops out.
+    pub fn add_row(&mut self, y: usize, row: Row) -> bool {
+        let pushed_out = self.field[0].iter().any(Option::is_some);
+        self.field.insert(y, row);
+        self.field.remove(0);
+        pushed_out
     }
 
     /*pub fn remove_row(&mut self, y: usize) {
@@ -220,61 +360,34 @@ impl PlayingField {
             *row = Some(destroying_block.clone());
         }
     }*/
+}
 
-    pub fn draw(&mut self, ctx: &mut Context) -> GameResult<()> {
-        let (window_width, window_height) = get_win_dim(ctx);
-        let offset = FIELD_ALIGN.to_offset(
-            (window_width, window_height),
-            (
-                FIELD_WIDTH as f32 * BLOCK_SIZE,
-                FIELD_HEIGHT_VIS as f32 * BLOCK_SIZE,
-            ),
-        );
-        for i in 0..=FIELD_WIDTH {
-            let mut dp = DrawParam::default().dest([offset.0 + i as f32 * BLOCK_SIZE, offset.1]);
-            if i == 0 || i == FIELD_WIDTH {
-                dp = dp.color(colors::LGREY.into());
-            }
-            self.grid_line_mesh_v.draw(ctx, dp)?;
-        }
-        for i in 0..=FIELD_HEIGHT_VIS {
-            let mut dp = DrawParam::default().dest([offset.0, offset.1 + i as f32 * BLOCK_SIZE]);
-            if i == 0 || i == FIELD_HEIGHT_VIS {
-                dp = dp.color(colors::LGREY.into());
-            }
-            self.grid_line_mesh_h.draw(ctx, dp)?;
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        // println!("field off: {:?}", offset);
-        for (y, row) in self.field[FIELD_HEIGHT - FIELD_HEIGHT_VIS..]
-            .iter()
-            .enumerate()
-        {
-            for (x, maybe_block) in row.iter().enumerate() {
-                if let Some(block) = maybe_block {
-                    let mesh = if let Some(mesh) = self.meshes.get(&block.block_color()) {
-                        mesh.clone()
-                    } else {
-                        // println!("calling gen_mesh in draw. Not yet in hashmap");
-                        let mesh = block.gen_mesh(ctx, -40)?;
-                        self.meshes.insert(block.block_color(), mesh.clone());
-                        mesh
-                    };
-                    let offset = DrawParam::default().dest([
-                        x as f32 * BLOCK_SIZE + offset.0,
-                        y as f32 * BLOCK_SIZE + offset.1,
-                    ]);
-                    // println!("offset: {:?}", offset);
-                    mesh.draw(ctx, offset)?;
-                }
-            }
+    #[test]
+    fn board_sizes_from_text() {
+        assert_eq!(
+            BoardSize::from_text("4x40"),
+            Ok(BoardSize {
+                width: 4,
+                height: 40
+            })
+        );
+        for text in ["3x20", "10x4", "65x20", "10x65", "10", "tenxtwenty"].iter() {
+            assert!(BoardSize::from_text(text).is_err(), "{}", text);
         }
-        Ok(())
     }
 
-    fn field_from_vec_shallow(&mut self, field: Vec<[Option<Block>; FIELD_WIDTH]>) {
-        for (y, row) in field.iter().enumerate() {
-            self.set_row(y, *row);
+    #[test]
+    fn add_row_pushing_blocks_out_tops_out() {
+        let size = BoardSize::default();
+        let garbage = vec![Some(Block::new(BlockColor::Garbage)); size.width];
+        let mut field = PlayingField::with_size(size);
+        for _ in 0..field.height() {
+            assert!(!field.add_row(field.height(), garbage.clone()));
         }
+        assert!(field.add_row(field.height(), garbage));
     }
 }
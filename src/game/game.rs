@@ -1,11 +1,16 @@
+use crate::controls::{Action, Trigger};
 use crate::net::Netinfo;
+use crate::scenes::editor::EditorState;
 use crate::scenes::menu::MenuScreen;
 use crate::scenes::paused::PausedMenuState;
 use crate::scenes::playing::PlayingState;
 use crate::scenes::playing_online::PlayingOnlineState;
+use crate::scenes::replay::ReplayState;
+use crate::settings::Settings;
 use crate::util::{
     colors,
     types::{KeypressInfo, KeypressInfos},
+    Align, BetterText,
 };
 
 use ggez::event::{self, Button, EventHandler, GamepadId, KeyCode, KeyMods};
@@ -13,8 +18,16 @@ use ggez::graphics;
 use ggez::timer::delta;
 use ggez::{Context, GameResult};
 
-const LOOPING_KEYS_PLAYING: [KeyCode; 3] = [KeyCode::Down, KeyCode::Right, KeyCode::Left];
-const LOOPING_KEYS_MENU: [KeyCode; 2] = [KeyCode::Down, KeyCode::Up];
+const LOOPING_KEYS_ONLINE: [KeyCode; 6] = [
+    KeyCode::Down,
+    KeyCode::Up,
+    KeyCode::Left,
+    KeyCode::Right,
+    KeyCode::Back,
+    KeyCode::Delete,
+];
+const LOOPING_KEYS_EDITOR: [KeyCode; 4] =
+    [KeyCode::Down, KeyCode::Up, KeyCode::Left, KeyCode::Right];
 const KEYPRESS_INIT_DELAY: f64 = 0.182;
 const KEYPRESS_DELAY: f64 = 0.05;
 
@@ -23,14 +36,24 @@ pub struct Game {
     font: graphics::Font,
     state: GameState,
     keydown: KeypressInfos,
+    settings: Settings,
+    /// Shown over the menu until the next key or button press
+    notice: Option<String>,
 }
 
 impl Game {
-    pub fn new(_ctx: &mut Context, font: graphics::Font) -> GameResult<Game> {
+    pub fn new(
+        _ctx: &mut Context,
+        font: graphics::Font,
+        settings: Settings,
+        notice: Option<String>,
+    ) -> GameResult<Game> {
         Ok(Game {
             font,
             state: GameState::default(),
             keydown: KeypressInfos::empty(),
+            settings,
+            notice,
         })
     }
 }
@@ -45,16 +68,22 @@ impl EventHandler for Game {
         let mut kpis = self.keydown.kpis();
         for kpi in kpis.iter_mut() {
             use GameState::*;
+            // gameplay keys repeat by the player's handling in `PlayingState`
             let looping_keys: &[KeyCode] = match &self.state {
-                Playing(_) | PlayingOnline(_, _) => &LOOPING_KEYS_PLAYING,
                 Menu(screen) => {
                     if let MenuScreen::OnlineInput(_) = screen {
-                        &LOOPING_KEYS_MENU
+                        &LOOPING_KEYS_ONLINE
                     } else {
                         &[]
                     }
                 }
-                Paused(_, _) | PausedOnline(_, _, _) | Exiting => &[],
+                Editor(_) => &LOOPING_KEYS_EDITOR,
+                Playing(_)
+                | PlayingOnline(_, _)
+                | Paused(_, _)
+                | PausedOnline(_, _, _)
+                | Replay(_)
+                | Exiting => &[],
             };
 
             if !looping_keys.contains(&kpi.key) && kpi.repeat_count > 0 {
@@ -68,17 +97,26 @@ impl EventHandler for Game {
 
                 kpi.repeat_count += 1;
                 kpi.delta = 0.;
-                self.state.pressed(ctx, kpi.key);
+                self.state
+                    .pressed(ctx, Trigger::Key(kpi.key), &mut self.settings);
             }
         }
         self.keydown.set(kpis);
-        self.state.update(ctx)?;
+        self.state.update(ctx, &self.settings)?;
         Ok(())
     }
 
     fn draw(&mut self, ctx: &mut Context) -> GameResult<()> {
         // let font: Font = self.font;
-        self.state.draw(ctx, self.font)
+        self.state.draw(ctx, self.font, &self.settings)?;
+        if let (Some(notice), GameState::Menu(_)) = (&self.notice, &self.state) {
+            BetterText::new(notice, self.font)
+                .align(Align::BR(20., 20.))
+                .size(11)
+                .color(colors::LRED)
+                .draw(ctx)?;
+        }
+        Ok(())
     }
 
     fn key_down_event(
@@ -89,34 +127,44 @@ impl EventHandler for Game {
         _repeat: bool,
     ) {
         if !self.keydown.contains(keycode) {
+            self.notice = None;
             let key_info = KeypressInfo::new(keycode);
-            self.state.pressed(ctx, keycode);
+            self.state
+                .pressed(ctx, Trigger::Key(keycode), &mut self.settings);
             self.keydown.push(key_info);
         }
     }
+    fn text_input_event(&mut self, _ctx: &mut Context, character: char) {
+        self.state.typed(character);
+    }
+
     fn key_up_event(&mut self, _ctx: &mut Context, keycode: KeyCode, _keymods: KeyMods) {
         self.keydown.pop_if_exists(keycode);
+        self.state.released(Trigger::Key(keycode), &self.settings);
     }
 
     fn gamepad_button_down_event(&mut self, ctx: &mut Context, btn: Button, _id: GamepadId) {
-        if let Some(keycode) = btn_to_keycode(btn) {
-            if !self.keydown.contains(keycode) {
-                let key_info = KeypressInfo::new(keycode);
-                self.state.pressed(ctx, keycode);
-                self.keydown.push(key_info);
-            }
-        }
+        self.notice = None;
+        self.state
+            .pressed(ctx, Trigger::Button(btn), &mut self.settings);
     }
 
     fn gamepad_button_up_event(&mut self, _ctx: &mut Context, btn: Button, _id: GamepadId) {
-        if let Some(keycode) = btn_to_keycode(btn) {
-            self.keydown.pop_if_exists(keycode);
-        }
+        self.state.released(Trigger::Button(btn), &self.settings);
     }
 
     fn resize_event(&mut self, ctx: &mut Context, width: f32, height: f32) {
         graphics::set_screen_coordinates(ctx, graphics::Rect::new(0.0, 0.0, width, height))
             .unwrap();
+        self.settings.window_width = width;
+        self.settings.window_height = height;
+    }
+
+    fn quit_event(&mut self, _ctx: &mut Context) -> bool {
+        if let Err(err) = self.settings.save() {
+            println!("Could not save settings:\n{}", err);
+        }
+        false
     }
 }
 
@@ -126,6 +174,8 @@ pub enum GameState {
     Paused(PlayingState, PausedMenuState),
     PlayingOnline(PlayingOnlineState, Netinfo),
     PausedOnline(PlayingOnlineState, PausedMenuState, Netinfo),
+    Replay(ReplayState),
+    Editor(EditorState),
     Exiting,
 }
 
@@ -133,8 +183,13 @@ impl GameState {
     pub fn default() -> GameState {
         GameState::Menu(MenuScreen::default())
     }
-    pub fn playing_online(ctx: &mut Context, ps: Option<PlayingState>, n: Netinfo) -> GameState {
-        let ps = ps.unwrap_or_else(|| PlayingState::new(ctx, true));
+    pub fn playing_online(
+        ctx: &mut Context,
+        ps: Option<PlayingState>,
+        n: Netinfo,
+        settings: &Settings,
+    ) -> GameState {
+        let ps = ps.unwrap_or_else(|| PlayingState::new(ctx, true, settings));
         GameState::PlayingOnline(PlayingOnlineState::Connected(ps), n)
     }
     // pub fn playing() -> GameState {
@@ -147,7 +202,12 @@ impl GameState {
     //     GameState::PausedOnline(playing_state, PausedMenuState::default(), net)
     // }
 
-    pub fn draw(&mut self, ctx: &mut Context, font: graphics::Font) -> GameResult<()> {
+    pub fn draw(
+        &mut self,
+        ctx: &mut Context,
+        font: graphics::Font,
+        settings: &Settings,
+    ) -> GameResult<()> {
         use GameState::*;
         match self {
             Playing(ref mut playing_state) => {
@@ -157,19 +217,21 @@ impl GameState {
                 playing_state.draw(ctx, font)?;
                 paused_menu.draw(ctx, font)?;
             }
-            PlayingOnline(ref mut playing_online_state, _) => {
-                playing_online_state.draw(ctx, font)?;
+            PlayingOnline(ref mut playing_online_state, ref net) => {
+                playing_online_state.draw(ctx, font, &net.enemy)?;
             }
-            PausedOnline(ref mut playing_online_state, ref paused_menu, _) => {
-                playing_online_state.draw(ctx, font)?;
+            PausedOnline(ref mut playing_online_state, ref paused_menu, ref net) => {
+                playing_online_state.draw(ctx, font, &net.enemy)?;
                 paused_menu.draw(ctx, font)?;
             }
-            Menu(ref mut menu_screen) => menu_screen.draw(ctx, font)?,
+            Replay(ref mut replay_state) => replay_state.draw(ctx, font)?,
+            Editor(ref mut editor_state) => editor_state.draw(ctx, font)?,
+            Menu(ref mut menu_screen) => menu_screen.draw(ctx, font, settings)?,
             Exiting => {}
         }
         graphics::present(ctx)
     }
-    pub fn update(&mut self, ctx: &mut Context) -> GameResult<()> {
+    pub fn update(&mut self, ctx: &mut Context, settings: &Settings) -> GameResult<()> {
         use GameState::*;
         let dt = delta(ctx).as_secs_f64();
         match self {
@@ -183,58 +245,85 @@ impl GameState {
                 if let Some(playing_state) = playing_online_state.as_option() {
                     net.receive(playing_state);
                     if !net.enemy.game_over {
-                        let prev_state = playing_state.clone();
                         let optional_new_state = playing_state.update(ctx)?;
-                        if let Err(_msg) = playing_state.update_net(dt, prev_state, net) {
-                            // TODO
-                        }
-                        if let Some(new_state) = optional_new_state {
+                        if let Err(msg) = playing_state.update_net(dt, net) {
+                            // also leaves the pause menu, there is nothing to resume
+                            *self =
+                                PlayingOnline(PlayingOnlineState::Disconnected(msg), net.clone());
+                        } else if let Some(new_state) = optional_new_state {
                             *self = new_state;
                         }
                     }
                 }
             }
             Menu(screen) => {
-                if let Some(new_state) = screen.update(ctx) {
+                if let Some(new_state) = screen.update(ctx, settings) {
                     *self = new_state;
                 }
             }
-            Paused(_, _) => {}
+            Replay(ref mut replay_state) => replay_state.update(ctx),
+            Paused(_, _) | Editor(_) => {}
             Exiting => {} // Menu(ref mut menu_state) => menu_state.update(ctx)?,
         }
         Ok(())
     }
 
-    pub fn pressed(&mut self, ctx: &mut Context, key: KeyCode) {
+    pub fn pressed(&mut self, ctx: &mut Context, trigger: Trigger, settings: &mut Settings) {
         use GameState::*;
+        let action = settings.controls.action(trigger);
         if let Some(new_state) = match self {
-            Playing(ref mut playing_state) => playing_state.pressed(ctx, key, None),
+            Playing(ref mut playing_state) => {
+                action.and_then(|action| playing_state.pressed(action, None))
+            }
             PlayingOnline(ref mut playing_online_state, ref mut net) => {
-                playing_online_state.pressed(ctx, key, net)
+                action.and_then(|action| playing_online_state.pressed(ctx, action, net))
             }
-            Paused(ref playing_state, ref mut paused_menu) => {
-                paused_menu.pressed(key, playing_state)
+            Paused(ref playing_state, ref mut paused_menu) => match action {
+                Some(Action::Pause) => Some(Playing(playing_state.resumed())),
+                _ => trigger
+                    .menu_key()
+                    .and_then(|key| paused_menu.pressed(key, playing_state)),
+            },
+            Replay(ref mut replay_state) => {
+                trigger.menu_key().and_then(|key| replay_state.pressed(key))
             }
-            Menu(ref mut menu_state) => menu_state.pressed(ctx, key),
+            Editor(ref mut editor_state) => trigger
+                .menu_key()
+                .and_then(|key| editor_state.pressed(ctx, key, settings)),
+            Menu(ref mut menu_state) => menu_state.pressed(ctx, trigger, settings),
             _ => None,
         } {
             *self = new_state;
         }
     }
-}
 
-fn btn_to_keycode(btn: Button) -> Option<KeyCode> {
-    use Button::*;
-    use KeyCode::*;
-    match btn {
-        DPadDown => Some(Down),
-        DPadLeft => Some(Left),
-        DPadRight => Some(Right),
-        DPadUp => Some(Up),
-        East => Some(Tab),
-        South => Some(Space),
-        LeftTrigger | RightTrigger => Some(Q),
-        Start | Select => Some(Escape),
-        _ => None,
+    /// Passes on a character from ggez's text input to the screen that takes text
+    pub fn typed(&mut self, character: char) {
+        if let GameState::Menu(MenuScreen::OnlineInput(ref mut state)) = self {
+            state.typed(character);
+        }
+    }
+
+    pub fn released(&mut self, trigger: Trigger, settings: &Settings) {
+        use GameState::*;
+        if let Editor(ref mut editor_state) = self {
+            if let Some(key) = trigger.menu_key() {
+                editor_state.released(key);
+            }
+            return;
+        }
+        let action = match settings.controls.action(trigger) {
+            Some(action) => action,
+            None => return,
+        };
+        match self {
+            Playing(ref mut playing_state) => playing_state.released(action),
+            PlayingOnline(ref mut playing_online_state, _) => {
+                if let Some(playing_state) = playing_online_state.as_option() {
+                    playing_state.released(action);
+                }
+            }
+            _ => {}
+        }
     }
 }
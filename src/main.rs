@@ -1,29 +1,36 @@
 mod block;
+mod controls;
+mod engine;
 mod field;
 mod game;
 mod net;
 mod piece;
+mod puzzles;
+mod records;
+mod render;
 mod scenes;
+mod settings;
 mod util;
 
 extern crate rand;
 
-use block::BLOCK_SIZE;
-use field::*;
 use game::*;
 use ggez::{
     conf, event,
     graphics::{screen_coordinates, Font},
     Context, ContextBuilder,
 };
-use scenes::playing::sidebar::SIDEBAR_WIDTH;
-
-const BOTTOM_MARGIN: f32 = 40.;
-pub const DEFAULT_WINDOW_WIDTH: f32 = FIELD_WIDTH as f32 * BLOCK_SIZE + FIELD_OFF.0 + SIDEBAR_WIDTH;
-pub const DEFAULT_WINDOW_HEIGHT: f32 =
-    (FIELD_HEIGHT_VIS + 1) as f32 * BLOCK_SIZE + FIELD_OFF.1 + BOTTOM_MARGIN;
+use settings::Settings;
 
 fn main() {
+    let (settings, notice) = match Settings::load() {
+        Ok(settings) => (settings, None),
+        Err(err) => {
+            println!("{}", err);
+            let notice = "The settings could not be read, the defaults replace them on exit";
+            (Settings::default(), Some(notice.to_string()))
+        }
+    };
     let resource_dir = std::path::PathBuf::from("./resources");
     let (mut ctx, mut event_loop) = ContextBuilder::new("tetro-98", "ffactory")
         .window_setup(conf::WindowSetup {
@@ -32,14 +39,7 @@ fn main() {
             samples: conf::NumSamples::Two,
             ..Default::default()
         })
-        .window_mode(conf::WindowMode {
-            min_width: DEFAULT_WINDOW_WIDTH,
-            min_height: DEFAULT_WINDOW_HEIGHT,
-            width: DEFAULT_WINDOW_WIDTH,
-            height: DEFAULT_WINDOW_HEIGHT,
-            resizable: true,
-            ..Default::default()
-        })
+        .window_mode(settings.window_mode())
         .add_resource_path(resource_dir)
         .build()
         .expect("Could not create ggez context!");
@@ -48,7 +48,8 @@ fn main() {
         .expect("Could not load font!");
     // "C:\\Development/rust/ggez-test/resources/imagine.ttf",
 
-    let mut my_game = Game::new(&mut ctx, font).expect("Could not initialize game");
+    let mut my_game =
+        Game::new(&mut ctx, font, settings, notice).expect("Could not initialize game");
 
     match event::run(&mut ctx, &mut event_loop, &mut my_game) {
         Ok(_) => println!("Bye!"),
@@ -0,0 +1,212 @@
+use crate::block::BlockColor;
+use crate::engine::Engine;
+use crate::field::{BoardSize, FIELD_SIDE_MAX};
+use crate::piece::{HoldPiece, Piece};
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+
+/// Counts no real board comes close to, anything larger is rejected
+pub const PIECE_CELLS_MAX: usize = 25;
+pub const QUEUE_LEN_MAX: usize = 8;
+/// Pieces of the queue that are mirrored
+const MIRROR_QUEUE_LEN: usize = 5;
+
+/// The cells of a piece and its color, enough to draw it
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MirrorPiece {
+    pub cells: Vec<(i8, i8)>,
+    pub color: BlockColor,
+}
+
+impl MirrorPiece {
+    /// The falling piece is kept where it is, with y counting from the top visible row.
+    /// Pieces in hold and queue are anchored at their top left.
+    fn new(piece: &Piece, in_field: bool) -> MirrorPiece {
+        let offset = if in_field { None } else { Some((0, 0)) };
+        MirrorPiece {
+            cells: piece
+                .fields_tuple_offset(offset)
+                .into_iter()
+                .map(|(x, y)| (clamp_i8(x), clamp_i8(y)))
+                .collect(),
+            color: piece.color(),
+        }
+    }
+}
+
+/// Returns the new value if it differs from the old one
+fn changed<T: PartialEq + Clone>(old: &T, new: &T) -> Option<T> {
+    if old == new {
+        None
+    } else {
+        Some(new.clone())
+    }
+}
+
+fn clamp_i8(n: isize) -> i8 {
+    i8::try_from(n).unwrap_or(if n < 0 { i8::MIN } else { i8::MAX })
+}
+
+/// What changed on the sender's board since its last update.
+/// Parts that didn't change are left out.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BoardUpdate {
+    /// Columns and visible rows, sent first and whenever they change
+    pub size: Option<(u8, u8)>,
+    /// Visible rows by their index from the top
+    pub rows: Vec<(u8, Vec<Option<BlockColor>>)>,
+    pub piece: Option<Option<MirrorPiece>>,
+    pub hold: Option<Option<MirrorPiece>>,
+    pub queue: Option<Vec<MirrorPiece>>,
+    /// Garbage lines waiting to rise into the sender's field
+    pub pending: Option<u16>,
+}
+
+impl BoardUpdate {
+    pub fn is_empty(&self) -> bool {
+        self.size.is_none()
+            && self.rows.is_empty()
+            && self.piece.is_none()
+            && self.hold.is_none()
+            && self.queue.is_none()
+            && self.pending.is_none()
+    }
+
+    /// Rejects updates larger than any board, before they are applied
+    pub fn check(&self) -> Result<(), String> {
+        let too_large = |what: &str| Err(format!("Board update has too many {}", what));
+        if let Some((width, height)) = self.size {
+            if width as usize > FIELD_SIDE_MAX || height as usize > FIELD_SIDE_MAX {
+                return too_large("rows or columns");
+            }
+        }
+        if self.rows.len() > FIELD_SIDE_MAX
+            || self.rows.iter().any(|(_, row)| row.len() > FIELD_SIDE_MAX)
+        {
+            return too_large("cells");
+        }
+        let pieces = self.piece.iter().chain(self.hold.iter()).flatten();
+        if pieces
+            .chain(self.queue.iter().flatten())
+            .any(|p| p.cells.len() > PIECE_CELLS_MAX)
+        {
+            return too_large("blocks in a piece");
+        }
+        if self
+            .queue
+            .as_ref()
+            .map_or(false, |q| q.len() > QUEUE_LEN_MAX)
+        {
+            return too_large("pieces in the queue");
+        }
+        Ok(())
+    }
+}
+
+/// A copy of a board to draw: its visible rows, pieces and incoming garbage.
+/// The opponent's is built up from updates, our own is compared to the last one sent.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MirrorBoard {
+    pub size: BoardSize,
+    pub rows: Vec<Vec<Option<BlockColor>>>,
+    pub piece: Option<MirrorPiece>,
+    pub hold: Option<MirrorPiece>,
+    pub queue: Vec<MirrorPiece>,
+    pub pending: usize,
+}
+
+impl MirrorBoard {
+    /// A board with no rows, so the first update sends everything
+    pub fn new() -> MirrorBoard {
+        MirrorBoard {
+            size: BoardSize {
+                width: 0,
+                height: 0,
+            },
+            rows: vec![],
+            piece: None,
+            hold: None,
+            queue: vec![],
+            pending: 0,
+        }
+    }
+
+    pub fn from_engine(engine: &Engine) -> MirrorBoard {
+        let field = engine.field();
+        let hold = engine.hold_piece().map(|hold| match hold {
+            HoldPiece::Unlocked(p) | HoldPiece::Locked(p) => MirrorPiece::new(p, false),
+        });
+        MirrorBoard {
+            size: field.size(),
+            rows: field
+                .visible_rows()
+                .iter()
+                .map(|row| row.iter().map(|b| b.map(|b| b.block_color())).collect())
+                .collect(),
+            piece: engine.piece().map(|p| MirrorPiece::new(p, true)),
+            hold,
+            queue: engine
+                .next_pieces()
+                .iter()
+                .take(MIRROR_QUEUE_LEN)
+                .map(|p| MirrorPiece::new(p, false))
+                .collect(),
+            pending: engine.pending_garbage(),
+        }
+    }
+
+    /// Returns what changed from this board to `next`
+    pub fn diff(&self, next: &MirrorBoard) -> BoardUpdate {
+        BoardUpdate {
+            size: changed(&self.size, &next.size).map(|s| (s.width as u8, s.height as u8)),
+            rows: next
+                .rows
+                .iter()
+                .enumerate()
+                .filter(|(y, row)| self.size != next.size || self.rows.get(*y) != Some(row))
+                .map(|(y, row)| (y as u8, row.clone()))
+                .collect(),
+            piece: changed(&self.piece, &next.piece),
+            hold: changed(&self.hold, &next.hold),
+            queue: changed(&self.queue, &next.queue),
+            pending: changed(&self.pending, &next.pending).map(|p| p as u16),
+        }
+    }
+
+    /// Applies an update from the peer, rejecting rows that don't fit the board
+    pub fn apply(&mut self, update: BoardUpdate) -> Result<(), String> {
+        if let Some((width, height)) = update.size {
+            self.size = BoardSize {
+                width: width as usize,
+                height: height as usize,
+            };
+            self.rows = vec![vec![None; self.size.width]; self.size.height];
+        }
+        for (y, row) in update.rows {
+            if row.len() != self.size.width {
+                return Err(format!(
+                    "Row of {} cells on a board {}",
+                    row.len(),
+                    self.size.name()
+                ));
+            }
+            match self.rows.get_mut(y as usize) {
+                Some(old) => *old = row,
+                None => return Err(format!("Row {} is not on a board {}", y, self.size.name())),
+            }
+        }
+        if let Some(piece) = update.piece {
+            self.piece = piece;
+        }
+        if let Some(hold) = update.hold {
+            self.hold = hold;
+        }
+        if let Some(queue) = update.queue {
+            self.queue = queue;
+        }
+        if let Some(pending) = update.pending {
+            self.pending = pending as usize;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,8 @@
+mod mirror;
+mod netinfo;
+mod protocol;
+mod recent;
+
+pub use mirror::MirrorBoard;
+pub use netinfo::*;
+pub use recent::{RecentPeer, RecentPeers};
@@ -0,0 +1,362 @@
+extern crate crossbeam_channel;
+
+use super::mirror::MirrorBoard;
+use super::protocol::{Features, Message, PROTOCOL_VERSION};
+use crate::scenes::playing::PlayingState;
+use crate::scenes::playing_online::EnemyState;
+use crate::util::types::ToResTString;
+
+use crossbeam_channel::{unbounded, Receiver, Sender, TryRecvError};
+use laminar::{Config, Packet, Socket, SocketEvent};
+use std::net::{IpAddr, SocketAddr, ToSocketAddrs};
+use std::thread;
+use std::time::{Duration, Instant};
+
+pub const TIMEOUT: f64 = 2.;
+pub const HEARTBEAT_INTERVAL: f64 = 0.2;
+pub const DEFAULT_PORT: u16 = 55755;
+/// Seconds between two updates of our board to the peer
+const BOARD_INTERVAL: f64 = 0.05;
+/// Board updates go on their own ordered stream
+const BOARD_STREAM: u8 = 1;
+
+/// How long a host waits for someone to join
+const HOST_TIMEOUT: f64 = 30.;
+/// How long joining waits for the host to answer
+const JOIN_TIMEOUT: f64 = 10.;
+const HELLO_INTERVAL: f64 = 0.5;
+
+/// Which side of the handshake this game takes
+#[derive(Clone, Debug, PartialEq)]
+pub enum Role {
+    /// Waits on the port until a peer says hello
+    Host,
+    /// Says hello to the host with this name or address
+    Join(String),
+}
+
+/// Where to connect to, chosen before connecting
+#[derive(Clone, Debug, PartialEq)]
+pub struct NetConfig {
+    pub role: Role,
+    /// Local address to bind to, `0.0.0.0` for every interface
+    pub bind: String,
+    /// Port the host listens on and joining sends to
+    pub port: u16,
+}
+
+/// What a connection attempt in the background reports to the menu
+pub enum Progress {
+    /// The step it is at, to show to the player
+    Status(String),
+    Connected(Netinfo),
+    Failed(String),
+}
+
+/// A connection attempt running on a worker thread
+#[derive(Clone, Debug)]
+pub struct Connecting {
+    progress: Receiver<Progress>,
+    cancel: Sender<()>,
+}
+
+impl Connecting {
+    /// Starts connecting on a new thread
+    pub fn start(config: NetConfig) -> Connecting {
+        let (progress_sender, progress) = unbounded();
+        let (cancel, cancelled) = unbounded();
+        thread::spawn(move || {
+            let result = match Netinfo::new(&config, &progress_sender, &cancelled) {
+                Ok(net) => Progress::Connected(net),
+                Err(err) => Progress::Failed(err),
+            };
+            // nobody is listening any more if the menu was left
+            let _ = progress_sender.send(result);
+        });
+        Connecting { progress, cancel }
+    }
+
+    /// Returns the next report, if there is one
+    pub fn poll(&self) -> Option<Progress> {
+        match self.progress.try_recv() {
+            Ok(progress) => Some(progress),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => Some(Progress::Failed(
+                "The connection attempt stopped unexpectedly".to_string(),
+            )),
+        }
+    }
+
+    /// Stops the attempt. The worker notices within a few milliseconds.
+    pub fn cancel(&self) {
+        let _ = self.cancel.send(());
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Netinfo {
+    // my_addr: SocketConnectionState,
+    // my_addr: SocketAddr,
+    peer_addr: SocketAddr,
+    sender: Sender<Packet>,
+    receiver: Receiver<SocketEvent>,
+    /// Once the last clone is dropped the poll thread stops and frees the port
+    _poll_stop: Sender<bool>,
+    /// What both sides agreed on in the handshake
+    features: Features,
+    pub last_response: f64,
+    pub last_sent: f64,
+    /// Our board as the peer last heard of it
+    board_sent: MirrorBoard,
+    since_board: f64,
+    pub enemy: EnemyState,
+}
+
+impl Netinfo {
+    /// Binds the socket and runs the handshake for the chosen role.
+    /// Blocks until a peer is found, the handshake fails, it times out or is cancelled,
+    /// so it runs on the worker of `Connecting`.
+    fn new(
+        config: &NetConfig,
+        progress: &Sender<Progress>,
+        cancel: &Receiver<()>,
+    ) -> Result<Netinfo, String> {
+        let status = |text: String| {
+            let _ = progress.send(Progress::Status(text));
+        };
+        // joining binds to any free port, only the host needs a known one
+        let local_port = match config.role {
+            Role::Host => config.port,
+            Role::Join(_) => 0,
+        };
+        let host_addr = match &config.role {
+            Role::Host => None,
+            Role::Join(host) => {
+                status(format!("looking up {}", host));
+                Some(Self::resolve(host, config.port, &config.bind)?)
+            }
+        };
+
+        let mut sock_conf = Config::default();
+        sock_conf.heartbeat_interval = Some(Duration::from_millis(200));
+        let mut me = Socket::bind_with_config((config.bind.as_str(), local_port), sock_conf)
+            .map_err(|e| format!("Could not bind to {}:{}: {}", config.bind, local_port, e))?;
+        status(match host_addr {
+            Some(addr) => format!("saying hello to {}", addr),
+            None => format!("waiting for someone to join on port {}", config.port),
+        });
+
+        let (sender, receiver) = (me.get_packet_sender(), me.get_event_receiver());
+
+        let (ts, tr) = unbounded::<bool>();
+
+        let poll_thread = thread::spawn(move || loop {
+            match tr.try_recv() {
+                Ok(true) | Err(TryRecvError::Disconnected) => return,
+                Ok(false) | Err(TryRecvError::Empty) => {}
+            }
+            me.manual_poll(Instant::now());
+            std::thread::sleep(Duration::from_millis(50));
+        });
+
+        let result = Self::handshake(&sender, &receiver, host_addr, cancel);
+        match result {
+            Ok((peer_addr, features)) => Ok(Netinfo {
+                peer_addr,
+                sender,
+                receiver,
+                _poll_stop: ts,
+                features: features.common(Features::supported()),
+                last_response: 0.,
+                last_sent: 0.,
+                board_sent: MirrorBoard::new(),
+                since_board: BOARD_INTERVAL,
+                enemy: EnemyState::new(),
+            }),
+            Err(err) => {
+                // give the poll thread a moment to send out a rejection
+                std::thread::sleep(Duration::from_millis(100));
+                ts.send(true).unwrap();
+                poll_thread.join().unwrap();
+                Err(err)
+            }
+        }
+    }
+
+    /// Finds the address of a host by name, in the same family as the bind address
+    fn resolve(host: &str, port: u16, bind: &str) -> Result<SocketAddr, String> {
+        let ipv4 = bind.parse::<IpAddr>().map_or(true, |ip| ip.is_ipv4());
+        let mut addresses = (host, port)
+            .to_socket_addrs()
+            .map_err(|e| format!("Could not find host '{}': {}", host, e))?;
+        addresses
+            .find(|a| a.is_ipv4() == ipv4)
+            .ok_or_else(|| format!("'{}' has no address reachable from {}", host, bind))
+    }
+
+    /// Waits for a hello, saying hello to the host first when joining.
+    /// Returns the peer and the features it announced.
+    fn handshake(
+        sender: &Sender<Packet>,
+        receiver: &Receiver<SocketEvent>,
+        host_addr: Option<SocketAddr>,
+        cancel: &Receiver<()>,
+    ) -> Result<(SocketAddr, Features), String> {
+        let start = Instant::now();
+        let timeout = if host_addr.is_some() {
+            JOIN_TIMEOUT
+        } else {
+            HOST_TIMEOUT
+        };
+        let mut last_hello: Option<Instant> = None;
+        loop {
+            while let Ok(se) = receiver.try_recv() {
+                let p = match se {
+                    SocketEvent::Packet(p) => p,
+                    _ => continue,
+                };
+                // while joining only the host is listened to
+                if host_addr.map_or(false, |addr| addr != p.addr()) {
+                    continue;
+                }
+                // a hello or a rejection from another version, either way it can't be played with
+                if let Some(version) =
+                    Message::version(p.payload()).filter(|v| *v != PROTOCOL_VERSION)
+                {
+                    let _ = sender.send(Self::packet(p.addr(), &Message::Reject));
+                    return Err(Self::version_mismatch(version));
+                }
+                match Message::decode(p.payload()) {
+                    Ok(Message::Hello { features }) => {
+                        if host_addr.is_none() {
+                            let _ = sender.send(Self::packet(p.addr(), &Message::hello()));
+                        }
+                        return Ok((p.addr(), features));
+                    }
+                    Ok(_) => {}
+                    Err(err) => println!("Ignoring packet from {}: {}", p.addr(), err),
+                }
+            }
+
+            // a dropped menu cancels as well
+            if cancel.try_recv() != Err(TryRecvError::Empty) {
+                return Err("Cancelled".to_string());
+            }
+            let elapsed = start.elapsed().as_secs_f64();
+            if elapsed >= timeout {
+                return Err(match host_addr {
+                    Some(addr) => format!("{} did not answer within {} seconds", addr, timeout),
+                    None => format!("Nobody joined within {} seconds", timeout),
+                });
+            }
+            if let Some(addr) = host_addr {
+                if last_hello.map_or(true, |t| t.elapsed().as_secs_f64() >= HELLO_INTERVAL) {
+                    sender
+                        .send(Self::packet(addr, &Message::hello()))
+                        .map_err(|e| e.to_string())?;
+                    last_hello = Some(Instant::now());
+                }
+            }
+            std::thread::sleep(Duration::from_millis(20));
+        }
+    }
+
+    pub fn delta(&mut self, delta: f64) {
+        self.last_response += delta;
+        self.last_sent += delta;
+        self.since_board += delta;
+    }
+
+    pub fn heartbeat(&mut self) -> Result<(), String> {
+        self.send(Message::Heartbeat)
+    }
+
+    /// sends game over packet
+    pub fn game_over(&mut self) -> Result<(), String> {
+        self.send(Message::GameOver)
+    }
+
+    pub fn lines(&mut self, amount: usize) -> Result<(), String> {
+        self.send(Message::Attack(amount.min(u16::MAX as usize) as u16))
+    }
+
+    pub fn height(&mut self, height: usize) -> Result<(), String> {
+        if !self.features.contains(Features::HEIGHT) {
+            return Ok(());
+        }
+        self.send(Message::Height(height.min(u16::MAX as usize) as u16))
+    }
+
+    /// Sends what changed on our board since the last update, if the peer mirrors boards
+    pub fn board(&mut self, board: MirrorBoard) -> Result<(), String> {
+        if !self.features.contains(Features::MIRROR) || self.since_board < BOARD_INTERVAL {
+            return Ok(());
+        }
+        let update = self.board_sent.diff(&board);
+        if update.is_empty() {
+            return Ok(());
+        }
+        let payload = Message::Board(update).encode();
+        self.sender
+            .send(Packet::reliable_ordered(
+                self.peer_addr,
+                payload,
+                Some(BOARD_STREAM),
+            ))
+            .to_str_err()?;
+        self.board_sent = board;
+        self.since_board = 0.;
+        self.last_sent = 0.;
+        Ok(())
+    }
+
+    pub fn receive(&mut self, playing_state: &mut PlayingState) -> Option<PlayingState> {
+        while let Ok(se) = self.receiver.try_recv() {
+            if let SocketEvent::Packet(p) = se {
+                if p.addr() != self.peer_addr {
+                    println!("Ignoring packet from {}, it isn't the peer", p.addr());
+                    continue;
+                }
+                let message = match Message::decode(p.payload()) {
+                    Ok(message) => message,
+                    Err(err) => {
+                        println!("Ignoring packet from {}: {}", p.addr(), err);
+                        continue;
+                    }
+                };
+                match message {
+                    Message::GameOver => self.enemy.game_over = true,
+                    Message::Height(height) => self.enemy.height = height as usize,
+                    Message::Attack(lines) => playing_state.add_garbage_lines(lines as usize),
+                    Message::Board(update) => {
+                        if let Err(err) = self.enemy.board.apply(update) {
+                            println!("Ignoring board update from {}: {}", p.addr(), err);
+                        }
+                    }
+                    Message::Hello { .. } | Message::Reject | Message::Heartbeat => {}
+                }
+                self.last_response = 0.;
+            }
+        }
+        None
+    }
+
+    fn send(&mut self, message: Message) -> Result<(), String> {
+        self.sender
+            .send(Self::packet(self.peer_addr, &message))
+            .to_str_err()?;
+        self.last_sent = 0.;
+        Ok(())
+    }
+
+    fn packet(addr: SocketAddr, message: &Message) -> Packet {
+        Packet::reliable_unordered(addr, message.encode())
+    }
+
+    fn version_mismatch(version: u16) -> String {
+        format!(
+            "The peer speaks protocol version {}, this game speaks {}",
+            version, PROTOCOL_VERSION
+        )
+    }
+}
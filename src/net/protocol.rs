@@ -0,0 +1,223 @@
+use super::mirror::BoardUpdate;
+use bincode::Options;
+use serde::{Deserialize, Serialize};
+
+/// Raised whenever a message changes shape. Every packet carries it after the magic,
+/// peers on other versions are turned away.
+pub const PROTOCOL_VERSION: u16 = 3;
+/// Starts every packet so that anything else, like the text packets
+/// of older versions, is told apart from a message
+const MAGIC: [u8; 2] = *b"T9";
+/// The magic followed by the protocol version
+const HEADER_LEN: usize = MAGIC.len() + 2;
+/// A board update of the largest field with the largest pieces is about 9 KiB,
+/// larger packets are rejected without decoding. Laminar sends up to 16 KiB.
+const MESSAGE_LEN_MAX: u64 = 12 * 1024;
+/// More lines than any clear sends, and more than a field has
+const ATTACK_LINES_MAX: u16 = 64;
+const HEIGHT_MAX: u16 = 255;
+
+/// Optional parts of the protocol. Each side announces what it supports in its hello
+/// and both only use what both support.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Features {
+    bits: u32,
+}
+
+impl Features {
+    /// The peer reports its stack height after every piece
+    pub const HEIGHT: Features = Features { bits: 1 };
+    /// The peer streams its board, pieces and incoming garbage
+    pub const MIRROR: Features = Features { bits: 2 };
+
+    /// Everything this version can do
+    pub fn supported() -> Features {
+        Features {
+            bits: Self::HEIGHT.bits | Self::MIRROR.bits,
+        }
+    }
+
+    pub fn contains(self, feature: Features) -> bool {
+        self.bits & feature.bits == feature.bits
+    }
+
+    /// The features both sides support
+    pub fn common(self, other: Features) -> Features {
+        Features {
+            bits: self.bits & other.bits,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Message {
+    /// Opens the handshake, repeated until the peer answers
+    Hello {
+        features: Features,
+    },
+    /// Answers a packet of another protocol version, which is all its header tells
+    Reject,
+    Heartbeat,
+    GameOver,
+    /// Garbage lines to add to the receiver's field
+    Attack(u16),
+    /// Rows of the sender's stack
+    Height(u16),
+    /// What changed on the sender's board. Sent in order, as each builds on the last.
+    Board(BoardUpdate),
+}
+
+impl Message {
+    pub fn hello() -> Message {
+        Message::Hello {
+            features: Features::supported(),
+        }
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend(&PROTOCOL_VERSION.to_be_bytes());
+        bytes.extend(
+            options()
+                .serialize(self)
+                .expect("Messages always serialize"),
+        );
+        bytes
+    }
+
+    /// Returns the protocol version a packet was sent with, None if it isn't one of this game
+    pub fn version(bytes: &[u8]) -> Option<u16> {
+        if bytes.len() < HEADER_LEN || !bytes.starts_with(&MAGIC) {
+            return None;
+        }
+        Some(u16::from_be_bytes([
+            bytes[MAGIC.len()],
+            bytes[MAGIC.len() + 1],
+        ]))
+    }
+
+    /// Reads a packet, rejecting anything that isn't exactly one valid message
+    /// of this protocol version
+    pub fn decode(bytes: &[u8]) -> Result<Message, String> {
+        match Self::version(bytes) {
+            None => return Err("Not a message of this game".to_string()),
+            Some(version) if version != PROTOCOL_VERSION => {
+                return Err(format!("Message of protocol version {}", version))
+            }
+            Some(_) => {}
+        }
+        let message: Message = options()
+            .deserialize(&bytes[HEADER_LEN..])
+            .map_err(|e| e.to_string())?;
+        match message {
+            Message::Attack(lines) if lines > ATTACK_LINES_MAX => {
+                Err(format!("Attack of {} lines is too large", lines))
+            }
+            Message::Height(height) if height > HEIGHT_MAX => {
+                Err(format!("Height of {} is too large", height))
+            }
+            Message::Board(update) => update.check().map(|_| Message::Board(update)),
+            message => Ok(message),
+        }
+    }
+}
+
+/// Variable length integers keep packets small
+fn options() -> impl Options {
+    bincode::DefaultOptions::new()
+        .with_limit(MESSAGE_LEN_MAX)
+        .reject_trailing_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block::BlockColor;
+    use crate::field::FIELD_SIDE_MAX;
+    use crate::net::mirror::{MirrorPiece, PIECE_CELLS_MAX, QUEUE_LEN_MAX};
+
+    fn board_update(rows: usize) -> BoardUpdate {
+        BoardUpdate {
+            size: Some((10, 20)),
+            rows: (0..rows)
+                .map(|y| (y as u8, vec![Some(BlockColor::Garbage); 10]))
+                .collect(),
+            piece: Some(None),
+            hold: None,
+            queue: Some(vec![]),
+            pending: Some(3),
+        }
+    }
+
+    #[test]
+    fn messages_round_trip() {
+        let messages = vec![
+            Message::hello(),
+            Message::Reject,
+            Message::Heartbeat,
+            Message::GameOver,
+            Message::Attack(ATTACK_LINES_MAX),
+            Message::Height(HEIGHT_MAX),
+            Message::Board(board_update(2)),
+        ];
+        for message in messages {
+            assert_eq!(Message::decode(&message.encode()), Ok(message));
+        }
+    }
+
+    #[test]
+    fn largest_board_update_fits() {
+        let side = FIELD_SIDE_MAX as u8;
+        let piece = MirrorPiece {
+            cells: vec![(i8::MIN, i8::MAX); PIECE_CELLS_MAX],
+            color: BlockColor::Blocked,
+        };
+        let update = BoardUpdate {
+            size: Some((side, side)),
+            rows: (0..side)
+                .map(|y| (y, vec![Some(BlockColor::Blocked); FIELD_SIDE_MAX]))
+                .collect(),
+            piece: Some(Some(piece.clone())),
+            hold: Some(Some(piece.clone())),
+            queue: Some(vec![piece; QUEUE_LEN_MAX]),
+            pending: Some(u16::MAX),
+        };
+        let message = Message::Board(update);
+        let bytes = message.encode();
+        assert!(bytes.len() as u64 <= MESSAGE_LEN_MAX);
+        assert_eq!(Message::decode(&bytes), Ok(message));
+    }
+
+    #[test]
+    fn broken_packets_are_rejected() {
+        let hello = Message::hello().encode();
+
+        let mut magic = hello.clone();
+        magic[0] = b'X';
+        assert!(Message::decode(&magic).is_err());
+
+        let mut version = hello.clone();
+        version[MAGIC.len()..HEADER_LEN].copy_from_slice(&(PROTOCOL_VERSION + 1).to_be_bytes());
+        assert_eq!(Message::version(&version), Some(PROTOCOL_VERSION + 1));
+        assert!(Message::decode(&version).is_err());
+
+        let mut trailing = hello.clone();
+        trailing.push(0);
+        assert!(Message::decode(&trailing).is_err());
+
+        let board = Message::Board(board_update(2)).encode();
+        for len in 0..board.len() {
+            assert!(Message::decode(&board[..len]).is_err(), "{} bytes", len);
+        }
+
+        let mut too_long = hello[..HEADER_LEN].to_vec();
+        let update = board_update(MESSAGE_LEN_MAX as usize);
+        too_long.extend(
+            bincode::DefaultOptions::new()
+                .serialize(&Message::Board(update))
+                .unwrap(),
+        );
+        assert!(too_long.len() as u64 > MESSAGE_LEN_MAX);
+        assert!(Message::decode(&too_long).is_err());
+    }
+}
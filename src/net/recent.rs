@@ -0,0 +1,51 @@
+use crate::util::store::{self, Dir};
+use serde::{Deserialize, Serialize};
+
+const RECENT_PEERS_FILE: &str = "recent_peers.toml";
+const RECENT_PEERS_MAX: usize = 8;
+
+/// A host that was joined before
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RecentPeer {
+    /// Name or address as it was entered
+    pub address: String,
+    pub port: u16,
+}
+
+impl RecentPeer {
+    pub fn name(&self) -> String {
+        format!("{} port {}", self.address, self.port)
+    }
+}
+
+/// The hosts joined last, most recent first. Saved as TOML in the user's data directory.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default = "RecentPeers::default")]
+pub struct RecentPeers {
+    pub peers: Vec<RecentPeer>,
+}
+
+impl RecentPeers {
+    pub fn default() -> RecentPeers {
+        RecentPeers { peers: vec![] }
+    }
+
+    /// Loads the saved peers, starting empty if there are none or they can't be read
+    pub fn load() -> RecentPeers {
+        store::load(Dir::Data, RECENT_PEERS_FILE, RecentPeers::default).unwrap_or_else(|err| {
+            println!("{}", err);
+            RecentPeers::default()
+        })
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        store::save(Dir::Data, RECENT_PEERS_FILE, self)
+    }
+
+    /// Moves the peer to the front, dropping the oldest one if there are too many
+    pub fn add(&mut self, peer: RecentPeer) {
+        self.peers.retain(|p| *p != peer);
+        self.peers.insert(0, peer);
+        self.peers.truncate(RECENT_PEERS_MAX);
+    }
+}
@@ -1,38 +1,63 @@
+use super::kicks::KickTable;
 use super::*;
-#[derive(Clone, Debug, PartialEq)]
-pub enum PieceShape {
-    T,
-    L,
-    J,
-    S,
-    Z,
-    O,
-    I,
+use serde::{Deserialize, Serialize};
+
+/// A piece as data: the cells it covers, its color, where it spawns and how it kicks.
+/// Piece sets are built from these, see `PieceSet`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PieceShape {
+    letter: char,
+    /// Square grid of cells, row by row from the top
+    bitmap: Vec<bool>,
+    color: BlockColor,
+    kicks: KickTable,
+    /// Row of the top of the grid when it spawns, -1 being the row above the visible field
+    spawn_row: isize,
+    /// Whether it scores spins by the 3-corner rule like the T
+    spins: bool,
 }
 
 impl PieceShape {
-    pub fn all() -> Vec<PieceShape> {
-        use PieceShape::*;
-        vec![T, L, J, Z, S, O, I]
-        // vec![O, I]
+    pub fn new(
+        letter: char,
+        bitmap: Vec<bool>,
+        color: BlockColor,
+        kicks: KickTable,
+        spawn_row: isize,
+        spins: bool,
+    ) -> PieceShape {
+        PieceShape {
+            letter,
+            bitmap,
+            color,
+            kicks,
+            spawn_row,
+            spins,
+        }
+    }
+
+    pub fn letter(&self) -> char {
+        self.letter
     }
+
     pub fn block_color(&self) -> BlockColor {
-        use super::BlockColor::*;
-        use PieceShape::*;
-        match self {
-            T => Purple,
-            L => Orange,
-            J => Blue,
-            S => Green,
-            Z => Red,
-            O => Yellow,
-            I => Cyan,
-        }
-        // .into()
+        self.color
+    }
+
+    pub fn kicks(&self) -> &KickTable {
+        &self.kicks
+    }
+
+    pub fn spawn_row(&self) -> isize {
+        self.spawn_row
+    }
+
+    pub fn spins(&self) -> bool {
+        self.spins
     }
 
     pub fn as_field(&self, rotation: &RotationState) -> PieceField {
-        let mut field = PieceField::new(self.bitmap());
+        let mut field = PieceField::new(self.bitmap.clone());
         for _ in 0..rotation.count() {
             field.rotate();
         }
@@ -40,27 +65,7 @@ impl PieceShape {
     }
 
     pub fn width(&self) -> usize {
-        use PieceShape::*;
-        match self {
-            O => 2,
-            J | L | S | Z | T => 3,
-            I => 4,
-        }
-    }
-    fn bitmap(&self) -> Vec<bool> {
-        use PieceShape::*;
-        match self {
-            T => vec![0, 1, 0, 1, 1, 1, 0, 0, 0],
-            L => vec![0, 0, 1, 1, 1, 1, 0, 0, 0],
-            J => vec![1, 0, 0, 1, 1, 1, 0, 0, 0],
-            S => vec![0, 1, 1, 1, 1, 0, 0, 0, 0],
-            Z => vec![1, 1, 0, 0, 1, 1, 0, 0, 0],
-            O => vec![1, 1, 1, 1],
-            I => vec![0, 0, 0, 0, 1, 1, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0],
-        }
-        .iter()
-        .map(|n| n != &0)
-        .collect()
+        (self.bitmap.len() as f64).sqrt() as usize
     }
 
     /*pub fn fields(&self) -> Vec<GridPoint> {
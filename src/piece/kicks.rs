@@ -0,0 +1,301 @@
+use super::{PieceShape, RotationState};
+use serde::{Deserialize, Serialize};
+
+/// Offsets as (x, y) with y pointing up, the way the published tables list them
+#[rustfmt::skip]
+const JLSTZ_KICKS: [[(isize, isize); 5]; 8] = [
+    [(0, 0), (-1, 0), (-1, 1), (0, -2), (-1, -2)], // 0 -> R
+    [(0, 0), (1, 0), (1, -1), (0, 2), (1, 2)],     // R -> 0
+    [(0, 0), (1, 0), (1, -1), (0, 2), (1, 2)],     // R -> 2
+    [(0, 0), (-1, 0), (-1, 1), (0, -2), (-1, -2)], // 2 -> R
+    [(0, 0), (1, 0), (1, 1), (0, -2), (1, -2)],    // 2 -> L
+    [(0, 0), (-1, 0), (-1, -1), (0, 2), (-1, 2)],  // L -> 2
+    [(0, 0), (-1, 0), (-1, -1), (0, 2), (-1, 2)],  // L -> 0
+    [(0, 0), (1, 0), (1, 1), (0, -2), (1, -2)],    // 0 -> L
+];
+
+#[rustfmt::skip]
+const I_KICKS: [[(isize, isize); 5]; 8] = [
+    [(0, 0), (-2, 0), (1, 0), (-2, -1), (1, 2)],  // 0 -> R
+    [(0, 0), (2, 0), (-1, 0), (2, 1), (-1, -2)],  // R -> 0
+    [(0, 0), (-1, 0), (2, 0), (-1, 2), (2, -1)],  // R -> 2
+    [(0, 0), (1, 0), (-2, 0), (1, -2), (-2, 1)],  // 2 -> R
+    [(0, 0), (2, 0), (-1, 0), (2, 1), (-1, -2)],  // 2 -> L
+    [(0, 0), (-2, 0), (1, 0), (-2, -1), (1, 2)],  // L -> 2
+    [(0, 0), (1, 0), (-2, 0), (1, -2), (-2, 1)],  // L -> 0
+    [(0, 0), (-1, 0), (2, 0), (-1, 2), (2, -1)],  // 0 -> L
+];
+
+/// 180° kicks as used by SRS+. The guideline itself has no 180° rotation.
+#[rustfmt::skip]
+const HALF_KICKS: [[(isize, isize); 6]; 4] = [
+    [(0, 0), (0, 1), (1, 1), (-1, 1), (1, 0), (-1, 0)],     // 0 -> 2
+    [(0, 0), (1, 0), (1, 2), (1, 1), (0, 2), (0, 1)],       // R -> L
+    [(0, 0), (0, -1), (-1, -1), (1, -1), (-1, 0), (1, 0)],  // 2 -> 0
+    [(0, 0), (-1, 0), (-1, 2), (-1, 1), (0, 2), (0, 1)],    // L -> R
+];
+
+const ARS_KICKS: [(isize, isize); 3] = [(0, 0), (1, 0), (-1, 0)];
+const NO_KICKS: [(isize, isize); 1] = [(0, 0)];
+
+/// Names of the turns in the order of `quarter_index` and `half_index`
+const QUARTER_TURNS: [&str; 8] = ["0>R", "R>0", "R>2", "2>R", "2>L", "L>2", "L>0", "0>L"];
+const HALF_TURNS: [&str; 4] = ["0>2", "R>L", "2>0", "L>R"];
+
+/// The offsets a piece tries in order when it rotates under SRS, one list for every turn
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct KickTable {
+    pub name: String,
+    /// Offsets as (x, y) with y pointing up, by `quarter_index`
+    quarter: Vec<Vec<(isize, isize)>>,
+    /// Offsets as (x, y) with y pointing up, by `half_index`
+    half: Vec<Vec<(isize, isize)>>,
+}
+
+impl KickTable {
+    /// The table of the J, L, S, T and Z
+    pub fn jlstz() -> KickTable {
+        Self::from_consts("jlstz", &JLSTZ_KICKS)
+    }
+
+    /// The table of the I
+    pub fn i() -> KickTable {
+        Self::from_consts("i", &I_KICKS)
+    }
+
+    /// Rotates in place or not at all, like the O
+    pub fn none() -> KickTable {
+        KickTable {
+            name: "none".to_string(),
+            quarter: vec![NO_KICKS.to_vec(); QUARTER_TURNS.len()],
+            half: vec![NO_KICKS.to_vec(); HALF_TURNS.len()],
+        }
+    }
+
+    /// The tables every piece set can use without declaring them
+    pub fn built_in() -> Vec<KickTable> {
+        vec![Self::jlstz(), Self::i(), Self::none()]
+    }
+
+    fn from_consts(name: &str, quarter: &[[(isize, isize); 5]; 8]) -> KickTable {
+        KickTable {
+            name: name.to_string(),
+            quarter: quarter.iter().map(|kicks| kicks.to_vec()).collect(),
+            half: HALF_KICKS.iter().map(|kicks| kicks.to_vec()).collect(),
+        }
+    }
+
+    /// Reads a table from lines like `0>R 0,0 -1,0 -1,1`: a turn and the offsets to try,
+    /// with y pointing up. Every quarter turn needs a line, half turns without one
+    /// kick like the built-in tables.
+    pub fn from_lines(name: &str, lines: &[&str]) -> Result<KickTable, String> {
+        let mut quarter = vec![None; QUARTER_TURNS.len()];
+        let mut half: Vec<Option<Vec<(isize, isize)>>> = HALF_KICKS
+            .iter()
+            .map(|kicks| Some(kicks.to_vec()))
+            .collect();
+        for line in lines {
+            let mut words = line.split_whitespace();
+            let turn = words.next().unwrap_or("");
+            let slot = match (
+                QUARTER_TURNS.iter().position(|t| *t == turn),
+                HALF_TURNS.iter().position(|t| *t == turn),
+            ) {
+                (Some(i), _) => &mut quarter[i],
+                (None, Some(i)) => &mut half[i],
+                (None, None) => {
+                    return Err(format!("Unknown turn '{}' in kick table '{}'", turn, name))
+                }
+            };
+            let kicks = words
+                .map(|word| parse_offset(word).ok_or(word))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|word| {
+                    format!("'{}' in kick table '{}' is no offset like -1,2", word, name)
+                })?;
+            if kicks.is_empty() {
+                return Err(format!(
+                    "Turn {} of kick table '{}' has no offsets",
+                    turn, name
+                ));
+            }
+            *slot = Some(kicks);
+        }
+        let missing = QUARTER_TURNS
+            .iter()
+            .zip(quarter.iter())
+            .find(|(_, kicks)| kicks.is_none());
+        if let Some((turn, _)) = missing {
+            return Err(format!("Kick table '{}' has no line for {}", name, turn));
+        }
+        Ok(KickTable {
+            name: name.to_string(),
+            quarter: quarter.into_iter().flatten().collect(),
+            half: half.into_iter().flatten().collect(),
+        })
+    }
+
+    /// The offsets for a turn in field coordinates, so y points down
+    fn offsets(&self, start: &RotationState, end: &RotationState) -> Vec<(isize, isize)> {
+        let kicks = match (quarter_index(start, end), half_index(start, end)) {
+            (Some(i), _) => &self.quarter[i],
+            (None, Some(i)) => &self.half[i],
+            (None, None) => return NO_KICKS.to_vec(),
+        };
+        kicks.iter().map(|(x, y)| (*x, -*y)).collect()
+    }
+}
+
+/// Reads `x,y`
+fn parse_offset(text: &str) -> Option<(isize, isize)> {
+    let mut parts = text.splitn(2, ',');
+    let x = parts.next()?.parse().ok()?;
+    let y = parts.next()?.parse().ok()?;
+    Some((x, y))
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum RotationSystem {
+    /// Super Rotation System with the guideline kick tables
+    Srs,
+    /// Arika style: one step right, then one step left.
+    /// Only pieces in a 3x3 grid kick, so the I never does.
+    Ars,
+    /// Rotates in place or not at all
+    Classic,
+}
+
+impl RotationSystem {
+    pub fn all() -> Vec<RotationSystem> {
+        use RotationSystem::*;
+        vec![Srs, Ars, Classic]
+    }
+
+    pub fn name(self) -> &'static str {
+        use RotationSystem::*;
+        match self {
+            Srs => "SRS",
+            Ars => "ARS",
+            Classic => "Classic",
+        }
+    }
+
+    /// Returns the offsets to try in order when rotating from `start` to `end`.
+    /// Offsets are in field coordinates, so y points down.
+    pub fn kicks(
+        self,
+        shape: &PieceShape,
+        start: &RotationState,
+        end: &RotationState,
+    ) -> Vec<(isize, isize)> {
+        use RotationSystem::*;
+        match self {
+            Srs => shape.kicks().offsets(start, end),
+            Ars if shape.width() == 3 => ARS_KICKS.to_vec(),
+            Ars | Classic => NO_KICKS.to_vec(),
+        }
+    }
+
+    /// Whether 180° rotation is allowed at all
+    pub fn has_half_rotation(self) -> bool {
+        self != RotationSystem::Classic
+    }
+}
+
+fn quarter_index(start: &RotationState, end: &RotationState) -> Option<usize> {
+    use RotationState::*;
+    Some(match (start, end) {
+        (Rs0, Rs1) => 0,
+        (Rs1, Rs0) => 1,
+        (Rs1, Rs2) => 2,
+        (Rs2, Rs1) => 3,
+        (Rs2, Rs3) => 4,
+        (Rs3, Rs2) => 5,
+        (Rs3, Rs0) => 6,
+        (Rs0, Rs3) => 7,
+        _ => return None,
+    })
+}
+
+fn half_index(start: &RotationState, end: &RotationState) -> Option<usize> {
+    use RotationState::*;
+    Some(match (start, end) {
+        (Rs0, Rs2) => 0,
+        (Rs1, Rs3) => 1,
+        (Rs2, Rs0) => 2,
+        (Rs3, Rs1) => 3,
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::piece::PieceSet;
+    use RotationState::*;
+
+    /// A turn and the offsets it tries
+    type Turn = (RotationState, RotationState, [(isize, isize); 5]);
+
+    /// The published SRS offsets with y flipped to point down, as in the field
+    #[rustfmt::skip]
+    const SRS_JLSTZ: [Turn; 8] = [
+        (Rs0, Rs1, [(0, 0), (-1, 0), (-1, -1), (0, 2), (-1, 2)]),
+        (Rs1, Rs0, [(0, 0), (1, 0), (1, 1), (0, -2), (1, -2)]),
+        (Rs1, Rs2, [(0, 0), (1, 0), (1, 1), (0, -2), (1, -2)]),
+        (Rs2, Rs1, [(0, 0), (-1, 0), (-1, -1), (0, 2), (-1, 2)]),
+        (Rs2, Rs3, [(0, 0), (1, 0), (1, -1), (0, 2), (1, 2)]),
+        (Rs3, Rs2, [(0, 0), (-1, 0), (-1, 1), (0, -2), (-1, -2)]),
+        (Rs3, Rs0, [(0, 0), (-1, 0), (-1, 1), (0, -2), (-1, -2)]),
+        (Rs0, Rs3, [(0, 0), (1, 0), (1, -1), (0, 2), (1, 2)]),
+    ];
+
+    #[rustfmt::skip]
+    const SRS_I: [Turn; 8] = [
+        (Rs0, Rs1, [(0, 0), (-2, 0), (1, 0), (-2, 1), (1, -2)]),
+        (Rs1, Rs0, [(0, 0), (2, 0), (-1, 0), (2, -1), (-1, 2)]),
+        (Rs1, Rs2, [(0, 0), (-1, 0), (2, 0), (-1, -2), (2, 1)]),
+        (Rs2, Rs1, [(0, 0), (1, 0), (-2, 0), (1, 2), (-2, -1)]),
+        (Rs2, Rs3, [(0, 0), (2, 0), (-1, 0), (2, -1), (-1, 2)]),
+        (Rs3, Rs2, [(0, 0), (-2, 0), (1, 0), (-2, 1), (1, -2)]),
+        (Rs3, Rs0, [(0, 0), (1, 0), (-2, 0), (1, 2), (-2, -1)]),
+        (Rs0, Rs3, [(0, 0), (-1, 0), (2, 0), (-1, -2), (2, 1)]),
+    ];
+
+    fn shape(letter: char) -> PieceShape {
+        PieceSet::standard().shape(letter).unwrap()
+    }
+
+    #[test]
+    fn jlstz_kicks_match_srs() {
+        for letter in "JLSTZ".chars() {
+            for (start, end, expected) in SRS_JLSTZ.iter() {
+                let kicks = RotationSystem::Srs.kicks(&shape(letter), start, end);
+                assert_eq!(
+                    kicks,
+                    expected.to_vec(),
+                    "{} {:?} -> {:?}",
+                    letter,
+                    start,
+                    end
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn i_kicks_match_srs() {
+        for (start, end, expected) in SRS_I.iter() {
+            let kicks = RotationSystem::Srs.kicks(&shape('I'), start, end);
+            assert_eq!(kicks, expected.to_vec(), "I {:?} -> {:?}", start, end);
+        }
+    }
+
+    #[test]
+    fn o_does_not_kick() {
+        for (start, end, _) in SRS_JLSTZ.iter() {
+            let kicks = RotationSystem::Srs.kicks(&shape('O'), start, end);
+            assert_eq!(kicks, vec![(0, 0)], "O {:?} -> {:?}", start, end);
+        }
+    }
+}
@@ -1,9 +1,12 @@
 pub mod hold_piece;
-pub mod rot_steps;
+pub mod kicks;
 
 pub use hold_piece::*;
+pub use kicks::{KickTable, RotationSystem};
 pub use piece::*;
+pub use piece_set::PieceSet;
 
 mod abstraction;
 mod piece;
+mod piece_set;
 use crate::block::*;
@@ -0,0 +1,372 @@
+use super::{KickTable, PieceShape};
+use crate::block::BlockColor;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+const PIECE_SET_EXTENSION: &str = "pieces";
+/// Larger pieces don't fit the spawn area above the field
+const PIECE_SIZE_MAX: usize = 5;
+
+const TETROMINOES: &str = "name: Tetrominoes
+piece: T purple spin
+.#.
+###
+...
+piece: L orange
+..#
+###
+...
+piece: J blue
+#..
+###
+...
+piece: Z red
+##.
+.##
+...
+piece: S green
+.##
+##.
+...
+piece: O yellow kicks=none
+##
+##
+piece: I cyan kicks=i
+....
+####
+....
+....
+";
+
+/// The pieces in a 3x3 grid kick like the tetrominoes. The long ones kick so the middle
+/// of their 4x2 box stays in place, the I around its middle cell, up or sideways.
+const PENTOMINOES: &str = "name: Pentominoes
+kicks: long
+0>R 0,0 -1,1 -2,1 0,1 -1,2
+R>0 0,0 1,-1 0,-1 2,-1 1,0
+R>2 0,0 1,1 0,1 2,1 1,2
+2>R 0,0 -1,-1 -2,-1 0,-1 -1,0
+2>L 0,0 1,-1 0,-1 2,-1 1,0
+L>2 0,0 -1,1 -2,1 0,1 -1,2
+L>0 0,0 -1,-1 -2,-1 0,-1 -1,0
+0>L 0,0 1,1 0,1 2,1 1,2
+kicks: bar
+0>R 0,0 0,1 0,2 -1,0 1,0
+R>0 0,0 -1,0 1,0 -2,0 2,0
+R>2 0,0 -1,0 1,0 -2,0 2,0
+2>R 0,0 0,1 0,2 -1,0 1,0
+2>L 0,0 0,1 0,2 -1,0 1,0
+L>2 0,0 -1,0 1,0 -2,0 2,0
+L>0 0,0 -1,0 1,0 -2,0 2,0
+0>L 0,0 0,1 0,2 -1,0 1,0
+piece: F orange
+.##
+##.
+.#.
+piece: f blue
+##.
+.##
+.#.
+piece: P yellow
+##.
+##.
+#..
+piece: p yellow
+##.
+##.
+.#.
+piece: T purple
+###
+.#.
+.#.
+piece: U purple
+#.#
+###
+...
+piece: V cyan
+#..
+#..
+###
+piece: W green
+#..
+##.
+.##
+piece: X red
+.#.
+###
+.#.
+piece: Z red
+##.
+.#.
+.##
+piece: S green
+.##
+.#.
+##.
+piece: L orange kicks=long
+...#
+####
+....
+....
+piece: J blue kicks=long
+#...
+####
+....
+....
+piece: N green kicks=long
+##..
+.###
+....
+....
+piece: n red kicks=long
+..##
+###.
+....
+....
+piece: Y purple kicks=long
+.#..
+####
+....
+....
+piece: y purple kicks=long
+..#.
+####
+....
+....
+piece: I cyan kicks=bar spawn=-2
+.....
+.....
+#####
+.....
+.....
+";
+
+const MONOMINOES: &str = "name: Monominoes
+piece: M yellow kicks=none
+#
+";
+
+/// The pieces a game is played with.
+///
+/// In text it is a `name` line followed by the pieces, each a `piece` line and its grid,
+/// and the kick tables the set brings, each a `kicks` line and a line for every turn:
+/// ```text
+/// name: Trominoes
+/// kicks: corner
+/// 0>R 0,0 -1,0 0,1
+/// ...
+/// piece: L orange kicks=corner
+/// #.
+/// ##
+/// piece: I cyan kicks=i
+/// ...
+/// ###
+/// ...
+/// ```
+/// The piece line has the letter, the color and optionally `kicks=<table>`,
+/// `spawn=<row>` for the row the grid's top spawns at and `spin` for T-spins.
+/// Pieces kick with `jlstz` unless they name another table, `i` and `none` are
+/// built in too. See `KickTable::from_lines` for the turns.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PieceSet {
+    pub name: String,
+    pub shapes: Vec<PieceShape>,
+}
+
+impl PieceSet {
+    /// The seven tetrominoes
+    pub fn standard() -> PieceSet {
+        Self::from_text(TETROMINOES).expect("Bundled piece set is invalid")
+    }
+
+    pub fn bundled() -> Vec<PieceSet> {
+        [TETROMINOES, PENTOMINOES, MONOMINOES]
+            .iter()
+            .map(|text| Self::from_text(text).expect("Bundled piece set is invalid"))
+            .collect()
+    }
+
+    /// The bundled sets followed by those in the user's piece set directory.
+    /// Sets that can't be read are left out.
+    pub fn available() -> Vec<PieceSet> {
+        let mut sets = Self::bundled();
+        let mut paths: Vec<PathBuf> = Self::user_dir()
+            .and_then(|dir| fs::read_dir(dir).ok())
+            .map(|entries| entries.filter_map(|e| e.ok()).map(|e| e.path()).collect())
+            .unwrap_or_default();
+        paths.retain(|path| path.extension().map_or(false, |e| e == PIECE_SET_EXTENSION));
+        paths.sort();
+        for path in paths {
+            let set = fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|text| Self::from_text(&text));
+            match set {
+                Ok(set) => sets.push(set),
+                Err(err) => println!("Could not load piece set {}:\n{}", path.display(), err),
+            }
+        }
+        sets
+    }
+
+    fn user_dir() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join("tetro-98").join("pieces"))
+    }
+
+    /// Finds a piece by its letter, falling back to the other case
+    pub fn shape(&self, letter: char) -> Option<PieceShape> {
+        self.shapes
+            .iter()
+            .find(|shape| shape.letter() == letter)
+            .or_else(|| {
+                self.shapes
+                    .iter()
+                    .find(|shape| shape.letter().eq_ignore_ascii_case(&letter))
+            })
+            .cloned()
+    }
+
+    pub fn from_text(text: &str) -> Result<PieceSet, String> {
+        let mut name = None;
+        // piece and kick table sections, by their header and lines
+        let mut sections: Vec<(Section, &str, Vec<&str>)> = vec![];
+        for line in text.lines().map(str::trim).filter(|l| !l.is_empty()) {
+            if let Some(value) = line.strip_prefix("name:") {
+                name = Some(value.trim().to_string());
+            } else if let Some(value) = line.strip_prefix("piece:") {
+                sections.push((Section::Piece, value.trim(), vec![]));
+            } else if let Some(value) = line.strip_prefix("kicks:") {
+                sections.push((Section::Kicks, value.trim(), vec![]));
+            } else if let Some((_, _, lines)) = sections.last_mut() {
+                lines.push(line);
+            } else {
+                return Err(format!("Row '{}' comes before any piece", line));
+            }
+        }
+        let mut tables = KickTable::built_in();
+        for (_, header, lines) in sections.iter().filter(|s| s.0 == Section::Kicks) {
+            if tables.iter().any(|t| t.name == *header) {
+                return Err(format!("There are two kick tables called '{}'", header));
+            }
+            tables.push(KickTable::from_lines(header, lines)?);
+        }
+        let mut shapes: Vec<PieceShape> = vec![];
+        for (_, header, rows) in sections.iter().filter(|s| s.0 == Section::Piece) {
+            let shape = parse_shape(header, rows, &tables)?;
+            if shapes.iter().any(|s| s.letter() == shape.letter()) {
+                return Err(format!("There are two pieces called '{}'", shape.letter()));
+            }
+            shapes.push(shape);
+        }
+        if shapes.is_empty() {
+            return Err("A piece set needs at least one piece".to_string());
+        }
+        Ok(PieceSet {
+            name: name.ok_or_else(|| "A piece set needs a name".to_string())?,
+            shapes,
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Section {
+    Piece,
+    Kicks,
+}
+
+/// Reads a piece from its `piece` line without the key and the rows of its grid
+fn parse_shape(header: &str, rows: &[&str], tables: &[KickTable]) -> Result<PieceShape, String> {
+    let mut words = header.split_whitespace();
+    let letter = match words.next().map(|w| w.chars().collect::<Vec<_>>()) {
+        Some(chars) if chars.len() == 1 && chars[0].is_alphabetic() => chars[0],
+        _ => return Err(format!("'{}' does not start with a letter", header)),
+    };
+    let color = words
+        .next()
+        .and_then(BlockColor::from_name)
+        .ok_or_else(|| format!("Piece '{}' needs a color", letter))?;
+    let mut kicks = KickTable::jlstz();
+    let mut spawn_row = -1;
+    let mut spins = false;
+    for word in words {
+        if let Some(value) = word.strip_prefix("kicks=") {
+            kicks = tables
+                .iter()
+                .find(|table| table.name == value)
+                .cloned()
+                .ok_or_else(|| format!("Unknown kicks '{}' for piece '{}'", value, letter))?;
+        } else if let Some(value) = word.strip_prefix("spawn=") {
+            spawn_row = value
+                .parse()
+                .map_err(|_| format!("Spawn row '{}' of piece '{}' is no number", value, letter))?;
+        } else if word == "spin" {
+            spins = true;
+        } else {
+            return Err(format!("Unknown option '{}' for piece '{}'", word, letter));
+        }
+    }
+
+    let size = rows.len();
+    if size == 0 || size > PIECE_SIZE_MAX {
+        return Err(format!(
+            "The grid of piece '{}' needs 1 to {} rows",
+            letter, PIECE_SIZE_MAX
+        ));
+    }
+    let mut bitmap = vec![];
+    for row in rows {
+        let cells: Vec<char> = row.chars().collect();
+        if cells.len() != size {
+            return Err(format!(
+                "The grid of piece '{}' is not square: row '{}' is not {} wide",
+                letter, row, size
+            ));
+        }
+        for cell in cells {
+            bitmap.push(match cell {
+                '#' => true,
+                '.' => false,
+                _ => return Err(format!("Unknown cell '{}' in piece '{}'", cell, letter)),
+            });
+        }
+    }
+    if !bitmap.contains(&true) {
+        return Err(format!("Piece '{}' has no blocks", letter));
+    }
+    if spins && size != 3 {
+        return Err(format!(
+            "Piece '{}' can't spin, only pieces in a 3x3 grid can",
+            letter
+        ));
+    }
+    Ok(PieceShape::new(
+        letter, bitmap, color, kicks, spawn_row, spins,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bundled_sets_load() {
+        let sets = PieceSet::bundled();
+        assert_eq!(sets.len(), 3);
+        let pentominoes = &sets[1];
+        assert_eq!(pentominoes.shape('L').unwrap().kicks().name, "long");
+        assert_eq!(pentominoes.shape('I').unwrap().kicks().name, "bar");
+        assert_eq!(pentominoes.shape('F').unwrap().kicks().name, "jlstz");
+    }
+
+    #[test]
+    fn kick_tables_need_every_quarter_turn() {
+        let text = "name: Broken
+kicks: short
+0>R 0,0 1,0
+piece: M yellow kicks=short
+#
+";
+        assert!(PieceSet::from_text(text).is_err());
+        assert!(PieceSet::from_text("name: Unknown\npiece: M yellow kicks=short\n#").is_err());
+    }
+}
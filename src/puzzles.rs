@@ -0,0 +1,92 @@
+use crate::engine::{level::*, EngineConfig, Position};
+use ggez::{filesystem, Context};
+use std::io::Read;
+use std::path::PathBuf;
+
+const PUZZLE_DIR: &str = "/puzzles";
+const PUZZLE_EXTENSION: &str = "puzzle";
+
+/// A position with a goal. Written as a position in text with two more keys:
+/// ```text
+/// name: First Tetris
+/// goal: lines 4
+/// queue: I
+/// #########.
+/// ```
+/// The goal is one of `lines <n>`, `perfect clear` or `tst`.
+#[derive(Clone, Debug)]
+pub struct Puzzle {
+    /// Name of the file, used to remember that it was solved
+    pub id: String,
+    pub name: String,
+    pub goal: PuzzleGoal,
+    pub position: Position,
+}
+
+impl Puzzle {
+    pub fn from_text(id: &str, text: &str) -> Result<Puzzle, String> {
+        let mut name = None;
+        let mut goal = None;
+        let mut rest = String::new();
+        for line in text.lines() {
+            let trimmed = line.trim();
+            if let Some(value) = trimmed.strip_prefix("name:") {
+                name = Some(value.trim().to_string());
+            } else if let Some(value) = trimmed.strip_prefix("goal:") {
+                goal = Some(PuzzleGoal::from_text(value)?);
+            } else {
+                rest += line;
+                rest.push('\n');
+            }
+        }
+        let position = Position::from_text(&rest)?;
+        if position.queue.is_empty() && position.hold.is_none() {
+            return Err("A puzzle needs at least one piece".to_string());
+        }
+        Ok(Puzzle {
+            id: id.to_string(),
+            name: name.unwrap_or_else(|| id.to_string()),
+            goal: goal.ok_or_else(|| "A puzzle needs a goal".to_string())?,
+            position,
+        })
+    }
+
+    /// Loads the puzzles shipped in the resource directory, sorted by file name.
+    /// Puzzles that can't be read are left out.
+    pub fn bundled(ctx: &mut Context) -> Result<Vec<Puzzle>, String> {
+        let mut paths: Vec<PathBuf> = filesystem::read_dir(ctx, PUZZLE_DIR)
+            .map_err(|e| e.to_string())?
+            .filter(|path| path.extension().map_or(false, |e| e == PUZZLE_EXTENSION))
+            .collect();
+        paths.sort();
+        let mut puzzles = vec![];
+        for path in paths {
+            match Self::load(ctx, &path) {
+                Ok(puzzle) => puzzles.push(puzzle),
+                Err(err) => println!("Could not load puzzle {}:\n{}", path.display(), err),
+            }
+        }
+        Ok(puzzles)
+    }
+
+    fn load(ctx: &mut Context, path: &PathBuf) -> Result<Puzzle, String> {
+        let mut text = String::new();
+        filesystem::open(ctx, path)
+            .map_err(|e| e.to_string())?
+            .read_to_string(&mut text)
+            .map_err(|e| e.to_string())?;
+        let id = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default();
+        Self::from_text(&id, &text)
+    }
+
+    pub fn config(&self) -> EngineConfig {
+        EngineConfig {
+            mode: GameMode::Puzzle(self.goal),
+            position: Some(self.position.clone()),
+            ..EngineConfig::new()
+        }
+    }
+}
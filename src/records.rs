@@ -0,0 +1,156 @@
+use crate::util::store::{self, Dir};
+use serde::{Deserialize, Serialize};
+
+const RECORDS_FILE: &str = "records.toml";
+/// Entries kept on every leaderboard
+pub const LEADERBOARD_SIZE: usize = 10;
+
+/// A finished sprint
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SprintRecord {
+    pub lines: usize,
+    /// Seconds until the last line was cleared
+    pub time: f64,
+    /// Time at every 10 lines
+    pub splits: Vec<f64>,
+    pub pieces: usize,
+}
+
+/// A finished ultra
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct UltraRecord {
+    /// Length of the game in seconds
+    pub duration: u32,
+    pub score: usize,
+    pub lines: usize,
+    pub pieces: usize,
+}
+
+/// The player's personal bests. Saved as TOML in the user's data directory.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default = "Records::default")]
+pub struct Records {
+    /// Ids of the puzzles that were solved at least once.
+    /// Before the lists of records, TOML can't write plain values after a table.
+    pub solved_puzzles: Vec<String>,
+    /// The best sprint for every line goal
+    pub sprints: Vec<SprintRecord>,
+    /// The best ultras for every duration, highest score first
+    pub ultras: Vec<UltraRecord>,
+}
+
+impl Records {
+    pub fn default() -> Records {
+        Records {
+            solved_puzzles: vec![],
+            sprints: vec![],
+            ultras: vec![],
+        }
+    }
+
+    /// Loads the saved records, empty if there are none yet
+    pub fn load() -> Result<Records, String> {
+        store::load(Dir::Data, RECORDS_FILE, Records::default)
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        store::save(Dir::Data, RECORDS_FILE, self)
+    }
+
+    pub fn best_sprint(&self, lines: usize) -> Option<&SprintRecord> {
+        self.sprints.iter().find(|record| record.lines == lines)
+    }
+
+    /// Keeps the sprint if it beats the best one with the same goal.
+    /// Returns whether it did.
+    pub fn submit_sprint(&mut self, record: SprintRecord) -> bool {
+        match self.sprints.iter_mut().find(|r| r.lines == record.lines) {
+            Some(best) if best.time <= record.time => false,
+            Some(best) => {
+                *best = record;
+                true
+            }
+            None => {
+                self.sprints.push(record);
+                true
+            }
+        }
+    }
+
+    pub fn is_solved(&self, puzzle: &str) -> bool {
+        self.solved_puzzles.iter().any(|id| id == puzzle)
+    }
+
+    /// Remembers the puzzle as solved. Returns whether it wasn't before.
+    pub fn solve(&mut self, puzzle: &str) -> bool {
+        if self.is_solved(puzzle) {
+            return false;
+        }
+        self.solved_puzzles.push(puzzle.to_string());
+        true
+    }
+
+    pub fn ultra_leaderboard(&self, duration: u32) -> Vec<&UltraRecord> {
+        self.ultras
+            .iter()
+            .filter(|record| record.duration == duration)
+            .collect()
+    }
+
+    /// Puts the ultra on the leaderboard for its duration.
+    /// Returns its place, or None if it didn't make it.
+    pub fn submit_ultra(&mut self, record: UltraRecord) -> Option<usize> {
+        let duration = record.duration;
+        let place = self
+            .ultra_leaderboard(duration)
+            .iter()
+            .take_while(|r| r.score >= record.score)
+            .count();
+        if place >= LEADERBOARD_SIZE {
+            return None;
+        }
+        let i = self
+            .ultras
+            .iter()
+            .position(|r| r.duration == duration && r.score < record.score)
+            .unwrap_or_else(|| self.ultras.len());
+        self.ultras.insert(i, record);
+        // drop whatever fell off the board
+        let mut kept = 0;
+        self.ultras.retain(|r| {
+            if r.duration != duration {
+                return true;
+            }
+            kept += 1;
+            kept <= LEADERBOARD_SIZE
+        });
+        Some(place)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_round_trip() {
+        let mut records = Records::default();
+        records.submit_sprint(SprintRecord {
+            lines: 40,
+            time: 61.5,
+            splits: vec![15., 30.5, 46.],
+            pieces: 101,
+        });
+        records.submit_ultra(UltraRecord {
+            duration: 120,
+            score: 25000,
+            lines: 60,
+            pieces: 150,
+        });
+        records.solve("tutorial-1");
+
+        let text = toml::to_string(&records).unwrap();
+        let loaded: Records = toml::from_str(&text).unwrap();
+        assert_eq!(loaded, records);
+    }
+}
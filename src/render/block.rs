@@ -0,0 +1,172 @@
+use crate::block::{BlockColor, BlockColorLightness};
+use crate::util::colors;
+use ggez::graphics::{DrawMode, Mesh, MeshBuilder, Rect};
+use ggez::{Context, GameResult};
+
+pub fn gen_mesh(
+    ctx: &mut Context,
+    color: BlockColor,
+    shift_amount: i8,
+    block_size: f32,
+) -> GameResult<Mesh> {
+    // println!(
+    //     "generating block mesh! Color: {:?}. Reason: {}",
+    //     color, why
+    // );
+    use colors::*;
+
+    let blsi = block_size;
+    let efwi = blsi / 6.; // effect width
+    let ma = 3.; // margin
+    let mut mesh = MeshBuilder::new();
+    mesh.rectangle(
+        DrawMode::Fill(Default::default()),
+        Rect::new(0., 0., blsi, blsi),
+        shift(color.color_tuple(), shift_amount).into(),
+    );
+    let (top_shine_shift, left_shadow_shift) = match color.dark_or_light() {
+        BlockColorLightness::Dark => (-25, -45),
+
+        BlockColorLightness::Light => (-40, -15),
+    };
+    if color != BlockColor::Destroying {
+        mesh.polygon(
+            DrawMode::Fill(Default::default()),
+            &[
+                // shiny effect
+                [ma, ma],
+                [blsi - ma, ma],
+                [blsi - ma, ma + efwi],
+                [blsi / 2. - ma, blsi / 2. - ma / 2.],
+                [ma, ma + 2. * efwi],
+                [ma, ma],
+            ],
+            TOP_SHINE.into(),
+        )?
+        .polygon(
+            DrawMode::Fill(Default::default()),
+            &[
+                // dark bottom
+                [ma, blsi - ma],
+                [blsi - ma, blsi - ma],
+                [blsi, blsi],
+                [0., blsi],
+                [ma, blsi - ma],
+            ],
+            BOTTOM_SHADOW.into(),
+        )?
+        .polygon(
+            // top shine
+            DrawMode::Fill(Default::default()),
+            &[[0., 0.], [blsi, 0.], [blsi - ma, ma], [ma, ma], [0., 0.]],
+            shift_alpha(TOP_SHINE, top_shine_shift).into(),
+        )?
+        .polygon(
+            // left shadow
+            DrawMode::Fill(Default::default()),
+            &[[0., 0.], [ma, ma], [ma, blsi - ma], [0., blsi], [0., 0.]],
+            shift_alpha(BOTTOM_SHADOW, left_shadow_shift).into(),
+        )?
+        .polygon(
+            DrawMode::Fill(Default::default()),
+            &[
+                // right shadow
+                [blsi, 0.],
+                [blsi, blsi],
+                [blsi - ma, blsi - ma],
+                [blsi - ma, ma],
+                [blsi, 0.],
+            ],
+            shift_alpha(BOTTOM_SHADOW, -13).into(),
+        )?;
+    }
+    mesh.build(ctx)
+    // Ok(mesh)
+}
+
+#[allow(non_snake_case)]
+pub fn gen_ghost_mesh(ctx: &mut Context, color: BlockColor, block_size: f32) -> GameResult<Mesh> {
+    let blsi = block_size;
+    // println!(
+    //     "generating block ghost mesh! Color: {:?}, size: {}",
+    //     color, blsi
+    // );
+    let inner_margin_half = blsi / 5.;
+    let stroke_w = blsi / 12.;
+    let mut mesh = MeshBuilder::new();
+    let ghost_color = colors::shift(color.color_tuple(), -40).into();
+
+    // Top left
+    let poly_TL = &[
+        [stroke_w + 0., 0. + stroke_w],
+        [stroke_w + blsi / 2. - inner_margin_half, 0. + stroke_w],
+        [
+            stroke_w + blsi / 2. - inner_margin_half,
+            stroke_w + stroke_w,
+        ],
+        [stroke_w + stroke_w, stroke_w + stroke_w],
+        [
+            stroke_w + stroke_w,
+            blsi / 2. - inner_margin_half + stroke_w,
+        ],
+        [stroke_w + 0., blsi / 2. - inner_margin_half + stroke_w],
+        [stroke_w + 0., 0. + stroke_w],
+    ];
+
+    // Top right
+    let poly_TR = &[
+        [blsi / 2. + inner_margin_half - stroke_w, 0. + stroke_w],
+        [blsi - stroke_w, 0. + stroke_w],
+        [blsi - stroke_w, blsi / 2. - inner_margin_half + stroke_w],
+        [
+            blsi - stroke_w - stroke_w,
+            blsi / 2. - inner_margin_half + stroke_w,
+        ],
+        [blsi - stroke_w - stroke_w, stroke_w + stroke_w],
+        [
+            blsi / 2. + inner_margin_half - stroke_w,
+            stroke_w + stroke_w,
+        ],
+        [blsi / 2. + inner_margin_half - stroke_w, 0. + stroke_w],
+    ];
+
+    // Bottom right
+    let poly_BR = &[
+        [blsi - stroke_w, blsi / 2. + inner_margin_half - stroke_w],
+        [blsi - stroke_w, blsi - stroke_w],
+        [blsi / 2. + inner_margin_half - stroke_w, blsi - stroke_w],
+        [
+            blsi / 2. + inner_margin_half - stroke_w,
+            blsi - stroke_w - stroke_w,
+        ],
+        [blsi - stroke_w - stroke_w, blsi - stroke_w - stroke_w],
+        [
+            blsi - stroke_w - stroke_w,
+            blsi / 2. + inner_margin_half - stroke_w,
+        ],
+        [blsi - stroke_w, blsi / 2. + inner_margin_half - stroke_w],
+    ];
+
+    // Bottom left
+    let poly_BL = &[
+        [stroke_w + 0., blsi / 2. + inner_margin_half - stroke_w],
+        [
+            stroke_w + stroke_w,
+            blsi / 2. + inner_margin_half - stroke_w,
+        ],
+        [stroke_w + stroke_w, blsi - stroke_w - stroke_w],
+        [
+            stroke_w + blsi / 2. - inner_margin_half,
+            blsi - stroke_w - stroke_w,
+        ],
+        [stroke_w + blsi / 2. - inner_margin_half, blsi - stroke_w],
+        [stroke_w + 0., blsi - stroke_w],
+        [stroke_w + 0., blsi / 2. + inner_margin_half - stroke_w],
+    ];
+
+    for poly in &[poly_TL, poly_TR, poly_BR, poly_BL] {
+        mesh.polygon(DrawMode::Fill(Default::default()), *poly, ghost_color)?;
+    }
+
+    mesh.build(ctx)
+}
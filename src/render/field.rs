@@ -0,0 +1,72 @@
+use super::{block, Renderer};
+use crate::field::*;
+use crate::get_win_dim;
+use crate::util::{colors, Align};
+use ggez::graphics::*;
+use ggez::{Context, GameResult};
+
+pub const FIELD_ALIGN: Align = Align::CC(0., 0.);
+
+pub const GRID_WIDTH: f32 = 1.;
+
+/// Space to keep free left of and above the field
+pub fn field_off(block_size: f32) -> (f32, f32) {
+    (block_size * 4., block_size)
+}
+
+impl Renderer {
+    /// Returns the alignment of the field's top left corner on screen
+    pub fn field_inner_align(&self, ctx: &mut Context) -> Align {
+        let field_offset = FIELD_ALIGN.to_offset(get_win_dim(ctx), self.field_dim());
+        Align::TL(field_offset.0, field_offset.1)
+    }
+
+    /// Size of the visible field on screen
+    pub fn field_dim(&self) -> (f32, f32) {
+        (
+            self.field_size.width as f32 * self.block_size,
+            self.field_size.height as f32 * self.block_size,
+        )
+    }
+
+    pub fn draw_field(&mut self, ctx: &mut Context, field: &PlayingField) -> GameResult<()> {
+        self.fit(ctx, field.size())?;
+        let block_size = self.block_size;
+        let BoardSize { width, height } = self.field_size;
+        let offset = FIELD_ALIGN.to_offset(get_win_dim(ctx), self.field_dim());
+        for i in 0..=width {
+            let mut dp = DrawParam::default().dest([offset.0 + i as f32 * block_size, offset.1]);
+            if i == 0 || i == width {
+                dp = dp.color(colors::LGREY.into());
+            }
+            self.grid_line_mesh_v.draw(ctx, dp)?;
+        }
+        for i in 0..=height {
+            let mut dp = DrawParam::default().dest([offset.0, offset.1 + i as f32 * block_size]);
+            if i == 0 || i == height {
+                dp = dp.color(colors::LGREY.into());
+            }
+            self.grid_line_mesh_h.draw(ctx, dp)?;
+        }
+
+        for (y, row) in field.visible_rows().iter().enumerate() {
+            for (x, maybe_block) in row.iter().enumerate() {
+                if let Some(block) = maybe_block {
+                    let mesh = if let Some(mesh) = self.block_meshes.get(&block.block_color()) {
+                        mesh.clone()
+                    } else {
+                        let mesh = block::gen_mesh(ctx, block.block_color(), -40, block_size)?;
+                        self.block_meshes.insert(block.block_color(), mesh.clone());
+                        mesh
+                    };
+                    let offset = DrawParam::default().dest([
+                        x as f32 * block_size + offset.0,
+                        y as f32 * block_size + offset.1,
+                    ]);
+                    mesh.draw(ctx, offset)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
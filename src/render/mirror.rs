@@ -0,0 +1,116 @@
+use super::{Renderer, HOLD_PIECE_SCALING};
+use crate::field::BoardSize;
+use crate::get_win_dim;
+use crate::net::MirrorBoard;
+use crate::util::{colors, Align};
+use ggez::graphics::*;
+use ggez::{Context, GameResult};
+
+const METER_WIDTH: f32 = 4.;
+const MIRROR_MARGIN: f32 = 10.;
+/// Kept free below the opponent's board for the statistics
+const MIRROR_BOTTOM: f32 = 110.;
+/// Side of a box in the opponent's hold and queue strip, in cells of the strip
+const STRIP_BOX: f32 = 5.;
+
+impl Renderer {
+    /// Draws a bar left of the field that rises with the garbage about to come in
+    pub fn draw_garbage_meter(&self, ctx: &mut Context, pending: usize) -> GameResult<()> {
+        if pending == 0 {
+            return Ok(());
+        }
+        let (field_x, field_y) = match self.field_inner_align(ctx) {
+            Align::TL(x, y) => (x, y),
+            _ => (0., 0.),
+        };
+        let field_h = self.field_dim().1;
+        let h = (pending as f32 * self.block_size).min(field_h);
+        let rect = Rect::new(
+            field_x - METER_WIDTH - 2.,
+            field_y + field_h - h,
+            METER_WIDTH,
+            h,
+        );
+        Mesh::new_rectangle(ctx, DrawMode::fill(), rect, colors::LRED.into())?
+            .draw(ctx, DrawParam::default())
+    }
+
+    /// Draws a small copy of the opponent's board below the hold box, with its garbage
+    /// meter on the left and its hold and next pieces underneath.
+    /// Nothing is drawn if the window leaves no room for it.
+    pub fn draw_mirror(&self, ctx: &mut Context, board: &MirrorBoard) -> GameResult<()> {
+        let BoardSize { width, height } = board.size;
+        if width == 0 || height == 0 {
+            return Ok(());
+        }
+        let (field_x, _) = match self.field_inner_align(ctx) {
+            Align::TL(x, y) => (x, y),
+            _ => (0., 0.),
+        };
+        let win_h = get_win_dim(ctx).1;
+        let top = 50. + 3. * HOLD_PIECE_SCALING * self.block_size + 50.;
+        let left = MIRROR_MARGIN + METER_WIDTH + 2.;
+        let room_w = field_x - left - MIRROR_MARGIN - METER_WIDTH;
+        // the strip below takes a cell of space and a box of half sized cells
+        let room_h = win_h - top - MIRROR_BOTTOM;
+        let cell = (room_w / width as f32)
+            .min(room_h / (height as f32 + STRIP_BOX / 2. + 1.))
+            .min(self.block_size / 2.)
+            .floor();
+        if cell < 2. {
+            return Ok(());
+        }
+        let (board_w, board_h) = (width as f32 * cell, height as f32 * cell);
+
+        let mut mesh = MeshBuilder::new();
+        mesh.rectangle(
+            DrawMode::stroke(1.),
+            Rect::new(left, top, board_w, board_h),
+            colors::LGREY.into(),
+        );
+        let mut fill = |x: isize, y: isize, color: Color| {
+            if x >= 0 && y >= 0 && (x as usize) < width && (y as usize) < height {
+                let rect = Rect::new(left + x as f32 * cell, top + y as f32 * cell, cell, cell);
+                mesh.rectangle(DrawMode::fill(), rect, color);
+            }
+        };
+        for (y, row) in board.rows.iter().enumerate() {
+            for (x, color) in row.iter().enumerate() {
+                if let Some(color) = color {
+                    fill(x as isize, y as isize, color.color_tuple().into());
+                }
+            }
+        }
+        if let Some(piece) = &board.piece {
+            for (x, y) in piece.cells.iter() {
+                fill(*x as isize, *y as isize, piece.color.color_tuple().into());
+            }
+        }
+
+        if board.pending > 0 {
+            let h = (board.pending as f32 * cell).min(board_h);
+            let rect = Rect::new(MIRROR_MARGIN, top + board_h - h, METER_WIDTH, h);
+            mesh.rectangle(DrawMode::fill(), rect, colors::LRED.into());
+        }
+
+        // the hold piece first, then as many next pieces as fit
+        let small = cell / 2.;
+        let strip_y = top + board_h + cell;
+        let boxes = (board_w / (STRIP_BOX * small)).floor() as usize;
+        let strip = board.hold.iter().chain(board.queue.iter()).take(boxes);
+        for (i, piece) in strip.enumerate() {
+            let box_x = left + i as f32 * STRIP_BOX * small;
+            for (x, y) in piece.cells.iter() {
+                let rect = Rect::new(
+                    box_x + *x as f32 * small,
+                    strip_y + *y as f32 * small,
+                    small,
+                    small,
+                );
+                mesh.rectangle(DrawMode::fill(), rect, piece.color.color_tuple().into());
+            }
+        }
+
+        mesh.build(ctx)?.draw(ctx, DrawParam::default())
+    }
+}
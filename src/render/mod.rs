@@ -0,0 +1,9 @@
+pub mod block;
+pub mod field;
+mod mirror;
+pub mod piece;
+mod renderer;
+
+pub use field::*;
+pub use piece::{PieceAppearance, HOLD_PIECE_SCALING};
+pub use renderer::*;
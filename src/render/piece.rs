@@ -0,0 +1,127 @@
+use super::{block, Renderer};
+use crate::block::BlockColor;
+use crate::engine::Engine;
+use crate::get_win_dim;
+use crate::piece::{HoldPiece, Piece};
+use crate::util::Align;
+use ggez::graphics::*;
+use ggez::{Context, GameResult};
+
+pub const HOLD_PIECE_SCALING: f32 = 0.65;
+
+#[derive(Clone, Debug)]
+pub struct PieceMeshes {
+    normal: Mesh,
+    ghost: Mesh,
+    blocked: Mesh,
+}
+
+#[derive(Clone, Debug)]
+pub enum PieceAppearance {
+    Normal,
+    Ghost,
+    Blocked,
+}
+
+impl Renderer {
+    fn piece_meshes(&mut self, ctx: &mut Context, color: BlockColor) -> GameResult<PieceMeshes> {
+        if let Some(meshes) = self.piece_meshes.get(&color) {
+            return Ok(meshes.clone());
+        }
+        let meshes = PieceMeshes {
+            normal: block::gen_mesh(ctx, color, 0, self.block_size)?,
+            ghost: block::gen_ghost_mesh(ctx, color, self.block_size)?,
+            blocked: block::gen_mesh(ctx, BlockColor::Blocked, 0, self.block_size)?,
+        };
+        self.piece_meshes.insert(color, meshes.clone());
+        Ok(meshes)
+    }
+
+    /// Draws a piece with its blocks scaled by `scaling`.
+    /// If ignore_offset is set, the piece is drawn relative to `align` only.
+    pub fn draw_piece(
+        &mut self,
+        ctx: &mut Context,
+        piece: &Piece,
+        scaling: f32,
+        align: Align,
+        ignore_offset: bool,
+        appearance: PieceAppearance,
+    ) -> GameResult<()> {
+        let meshes = self.piece_meshes(ctx, piece.color())?;
+        let mesh = match appearance {
+            PieceAppearance::Normal => &meshes.normal,
+            PieceAppearance::Ghost => &meshes.ghost,
+            PieceAppearance::Blocked => &meshes.blocked,
+        };
+
+        let block_size = self.block_size * scaling;
+        let window_offset: (f32, f32) = {
+            let mesh_dimensions = (
+                block_size * piece.width() as f32,
+                block_size * piece.width() as f32,
+            );
+            align.to_offset(get_win_dim(ctx), mesh_dimensions)
+        };
+
+        let fields = {
+            let tuple_offset = if ignore_offset { Some((0, 0)) } else { None };
+            piece.fields_tuple_offset(tuple_offset)
+        };
+        for (x, y) in fields {
+            let (x, y) = (x as f32 * block_size, y as f32 * block_size);
+            let mesh_offset = [x + window_offset.0, y + window_offset.1];
+            let param = DrawParam::default().dest(mesh_offset).scale([scaling; 2]);
+            mesh.draw(ctx, param)?;
+        }
+
+        Ok(())
+    }
+
+    /// Draws the engine's falling piece and its ghost inside the field
+    pub fn draw_falling_piece(&mut self, ctx: &mut Context, engine: &Engine) -> GameResult<()> {
+        let field_inner_align = self.field_inner_align(ctx);
+        if let Some(piece) = engine.piece() {
+            if let Some(ghost_piece) = engine.ghost_piece() {
+                self.draw_piece(
+                    ctx,
+                    &ghost_piece,
+                    1.,
+                    field_inner_align,
+                    false,
+                    PieceAppearance::Ghost,
+                )?;
+            }
+            self.draw_piece(
+                ctx,
+                piece,
+                1.,
+                field_inner_align,
+                false,
+                PieceAppearance::Normal,
+            )?;
+        }
+        Ok(())
+    }
+
+    pub fn draw_hold_piece(
+        &mut self,
+        ctx: &mut Context,
+        hold_piece: &HoldPiece,
+        align: Align,
+    ) -> GameResult<()> {
+        let (piece, appearance) = match hold_piece {
+            HoldPiece::Unlocked(p) => (p, PieceAppearance::Normal),
+            HoldPiece::Locked(p) => (p, PieceAppearance::Blocked),
+        };
+        let adj_scaling = 3. / piece.width() as f32;
+        self.draw_piece(
+            ctx,
+            piece,
+            adj_scaling * HOLD_PIECE_SCALING,
+            align,
+            true,
+            appearance,
+        )
+    }
+}
@@ -0,0 +1,102 @@
+use super::field::GRID_WIDTH;
+use super::piece::PieceMeshes;
+use crate::block::BlockColor;
+use crate::field::BoardSize;
+use crate::get_win_dim;
+use crate::settings::window_size_for;
+use crate::util::colors;
+use ggez::graphics::{DrawMode, Mesh, Rect};
+use ggez::{Context, GameResult};
+use std::collections::HashMap;
+
+/// Blocks don't get smaller than this to fit a big field into the window
+const MIN_FIT_BLOCK_SIZE: f32 = 6.;
+
+/// Draws the state of an engine. Holds every mesh so they only get built once.
+#[derive(Clone, Debug)]
+pub struct Renderer {
+    pub(super) block_size: f32,
+    /// The block size from the settings, which smaller blocks are used instead of
+    /// when the field doesn't fit the window
+    base_block_size: f32,
+    pub(super) field_size: BoardSize,
+    pub(super) block_meshes: HashMap<BlockColor, Mesh>,
+    pub(super) piece_meshes: HashMap<BlockColor, PieceMeshes>,
+    pub(super) grid_line_mesh_v: Mesh,
+    pub(super) grid_line_mesh_h: Mesh,
+}
+
+impl Renderer {
+    pub fn new(ctx: &mut Context, block_size: f32) -> GameResult<Renderer> {
+        let field_size = BoardSize::default();
+        let (grid_line_mesh_v, grid_line_mesh_h) = Self::grid_meshes(ctx, block_size, field_size)?;
+
+        Ok(Renderer {
+            block_size,
+            base_block_size: block_size,
+            field_size,
+            block_meshes: HashMap::new(),
+            piece_meshes: HashMap::new(),
+            grid_line_mesh_v,
+            grid_line_mesh_h,
+        })
+    }
+
+    fn grid_meshes(
+        ctx: &mut Context,
+        block_size: f32,
+        size: BoardSize,
+    ) -> GameResult<(Mesh, Mesh)> {
+        let vertical = Mesh::new_rectangle(
+            ctx,
+            DrawMode::Fill(Default::default()),
+            Rect::new(
+                -GRID_WIDTH / 2.,
+                -GRID_WIDTH / 2.,
+                GRID_WIDTH,
+                size.height as f32 * block_size,
+            ),
+            colors::BG_LINE_COLOR.into(),
+        )?;
+        let horizontal = Mesh::new_rectangle(
+            ctx,
+            DrawMode::Fill(Default::default()),
+            Rect::new(
+                -GRID_WIDTH / 2.,
+                -GRID_WIDTH / 2.,
+                size.width as f32 * block_size,
+                GRID_WIDTH,
+            ),
+            colors::BG_LINE_COLOR.into(),
+        )?;
+        Ok((vertical, horizontal))
+    }
+
+    /// Adapts to the size of the field and shrinks the blocks if it wouldn't fit the window
+    pub fn fit(&mut self, ctx: &mut Context, size: BoardSize) -> GameResult<()> {
+        let (win_w, win_h) = get_win_dim(ctx);
+        let mut block_size = self.base_block_size;
+        while block_size > MIN_FIT_BLOCK_SIZE {
+            let (w, h) = window_size_for(block_size, size);
+            if w <= win_w && h <= win_h {
+                break;
+            }
+            block_size -= 1.;
+        }
+        if size == self.field_size && block_size == self.block_size {
+            return Ok(());
+        }
+        let (vertical, horizontal) = Self::grid_meshes(ctx, block_size, size)?;
+        self.grid_line_mesh_v = vertical;
+        self.grid_line_mesh_h = horizontal;
+        self.block_meshes.clear();
+        self.piece_meshes.clear();
+        self.block_size = block_size;
+        self.field_size = size;
+        Ok(())
+    }
+
+    pub fn block_size(&self) -> f32 {
+        self.block_size
+    }
+}
@@ -0,0 +1,274 @@
+use crate::block::{Block, BlockColor};
+use crate::engine::{shapes_text, EngineConfig, Position};
+use crate::field::{BoardSize, PlayingField};
+use crate::game::GameState;
+use crate::piece::{HoldPiece, Piece, PieceSet};
+use crate::render::Renderer;
+use crate::scenes::menu::{cycle, MainMenuItem, MenuScreen};
+use crate::scenes::playing::{sidebar, PlayingState};
+use crate::settings::Settings;
+use crate::util::{clipboard, colors, Align, BetterText};
+
+use ggez::{event::KeyCode, graphics::*, Context, GameResult};
+use std::fs;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const PALETTE: [BlockColor; 8] = [
+    BlockColor::Purple,
+    BlockColor::Orange,
+    BlockColor::Blue,
+    BlockColor::Green,
+    BlockColor::Red,
+    BlockColor::Yellow,
+    BlockColor::Cyan,
+    BlockColor::Garbage,
+];
+const SWATCH_SIZE: f32 = 14.;
+
+/// Paints a board and the pieces to come, to play from it or share it as text
+#[derive(Clone, Debug)]
+pub struct EditorState {
+    position: Position,
+    renderer: Renderer,
+    /// Cell under the cursor, y counting from the top of the visible rows
+    cursor: (usize, usize),
+    color: usize, // index into `PALETTE`
+    /// Whether space is held, so moving the cursor paints
+    painting: bool,
+    message: Option<String>,
+}
+
+impl EditorState {
+    pub fn new(ctx: &mut Context, position: Position, settings: &Settings) -> EditorState {
+        let renderer = Renderer::new(ctx, settings.block_size).expect("Failed to build meshes");
+        let bottom = position.field.visible_height() - 1;
+        EditorState {
+            position,
+            renderer,
+            cursor: (0, bottom),
+            color: PALETTE.len() - 1,
+            painting: false,
+            message: None,
+        }
+    }
+
+    pub fn pressed(
+        &mut self,
+        ctx: &mut Context,
+        key: KeyCode,
+        settings: &Settings,
+    ) -> Option<GameState> {
+        use KeyCode::*;
+        let (x, y) = self.cursor;
+        let size = self.position.field.size();
+        match key {
+            Left => self.cursor.0 = x.saturating_sub(1),
+            Right => self.cursor.0 = (x + 1).min(size.width - 1),
+            Up => self.cursor.1 = y.saturating_sub(1),
+            Down => self.cursor.1 = (y + 1).min(size.height - 1),
+            Space => {
+                self.painting = true;
+                // painting a cell with its own color clears it
+                let color = PALETTE[self.color];
+                let cell = match self.cell() {
+                    Some(block) if block.block_color() == color => None,
+                    _ => Some(Block::new(color)),
+                };
+                self.set_cell(cell);
+                return None;
+            }
+            Delete => self.set_cell(None),
+            Tab => self.color = (self.color + 1) % PALETTE.len(),
+            Key1 | Key2 | Key3 | Key4 | Key5 | Key6 | Key7 | Key8 => {
+                self.color = key as usize - Key1 as usize
+            }
+            T | L | J | S | Z | O | I => {
+                let letter = format!("{:?}", key).chars().next().unwrap_or('T');
+                if let Some(shape) = PieceSet::standard().shape(letter) {
+                    self.position.queue.push(shape);
+                }
+            }
+            Back => {
+                self.position.queue.pop();
+            }
+            H => {
+                // cycles through no hold and every piece
+                let shapes = PieceSet::standard().shapes;
+                let i = self
+                    .position
+                    .hold
+                    .as_ref()
+                    .and_then(|hold| shapes.iter().position(|s| s == hold));
+                self.position.hold = match i {
+                    None => shapes.first().cloned(),
+                    Some(i) => shapes.get(i + 1).cloned(),
+                };
+            }
+            N => {
+                self.position = Position {
+                    field: PlayingField::with_size(size),
+                    ..Position::new()
+                };
+                self.message = Some("cleared the board".to_string());
+            }
+            B => {
+                // a board of another size starts empty
+                let size = cycle(&BoardSize::all(), size, true);
+                self.position.field = PlayingField::with_size(size);
+                self.cursor = (0, size.height - 1);
+                self.message = Some(format!("board size {}", size.name()));
+            }
+            C => {
+                self.message = Some(match clipboard::copy(self.position.to_text()) {
+                    Ok(()) => "copied the board".to_string(),
+                    Err(err) => format!("could not copy: {}", err),
+                });
+            }
+            V => {
+                let pasted = clipboard::paste().and_then(|text| Position::from_text(&text));
+                self.message = Some(match pasted {
+                    Ok(position) => {
+                        let size = position.field.size();
+                        self.cursor = (self.cursor.0.min(size.width - 1), size.height - 1);
+                        self.position = position;
+                        "pasted a board".to_string()
+                    }
+                    Err(err) => format!("could not paste: {}", err),
+                });
+            }
+            E => {
+                self.message = Some(match self.export() {
+                    Ok(path) => format!("exported to {}", path.display()),
+                    Err(err) => format!("could not export: {}", err),
+                });
+            }
+            Return => {
+                let config = EngineConfig {
+                    position: Some(self.position.clone()),
+                    ..EngineConfig::new()
+                };
+                let state = PlayingState::with_config(ctx, false, config, settings);
+                self.painting = false;
+                return Some(GameState::Playing(state.with_editor(self.clone())));
+            }
+            Escape => {
+                let main = MenuScreen::Main(MainMenuItem::Editor, EngineConfig::new());
+                return Some(GameState::Menu(main));
+            }
+            _ => {}
+        }
+        if self.painting && self.cursor != (x, y) {
+            self.set_cell(Some(Block::new(PALETTE[self.color])));
+        }
+        None
+    }
+
+    pub fn released(&mut self, key: KeyCode) {
+        if key == KeyCode::Space {
+            self.painting = false;
+        }
+    }
+
+    fn cell(&self) -> Option<Block> {
+        let (x, y) = self.cursor;
+        let field = &self.position.field;
+        field.get_cell(x, field.first_visible_row() + y)
+    }
+
+    fn set_cell(&mut self, cell: Option<Block>) {
+        let (x, y) = self.cursor;
+        let field = &mut self.position.field;
+        let y = field.first_visible_row() + y;
+        field.set_cell(x, y, cell);
+    }
+
+    /// Writes the board as text into the boards directory, named by the current time
+    fn export(&self) -> Result<PathBuf, String> {
+        let dir = dirs::data_dir()
+            .map(|dir| dir.join("tetro-98").join("boards"))
+            .ok_or_else(|| "No data directory found".to_string())?;
+        fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+        let millis = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(|e| e.to_string())?
+            .as_millis();
+        let path = dir.join(format!("{}.txt", millis));
+        fs::write(&path, self.position.to_text()).map_err(|e| e.to_string())?;
+        Ok(path)
+    }
+
+    pub fn draw(&mut self, ctx: &mut Context, font: Font) -> GameResult<()> {
+        clear(ctx, colors::BG.into());
+        self.renderer.draw_field(ctx, &self.position.field)?;
+        let hold = self
+            .position
+            .hold
+            .clone()
+            .map(|shape| HoldPiece::Unlocked(Piece::new(shape)));
+        let queue = self
+            .position
+            .queue
+            .iter()
+            .cloned()
+            .map(Piece::new)
+            .collect();
+        sidebar::draw(ctx, &mut self.renderer, hold.as_ref(), queue, font)?;
+
+        let block_size = self.renderer.block_size();
+        let (field_x, field_y) = match self.renderer.field_inner_align(ctx) {
+            Align::TL(x, y) => (x, y),
+            _ => (0., 0.),
+        };
+        let (x, y) = self.cursor;
+        Mesh::new_rectangle(
+            ctx,
+            DrawMode::stroke(2.),
+            Rect::new(
+                field_x + x as f32 * block_size,
+                field_y + y as f32 * block_size,
+                block_size,
+                block_size,
+            ),
+            colors::WHITE.into(),
+        )?
+        .draw(ctx, DrawParam::default())?;
+
+        for (i, color) in PALETTE.iter().enumerate() {
+            let y = 150. + i as f32 * (SWATCH_SIZE + 6.);
+            let rect = Rect::new(14., y, SWATCH_SIZE, SWATCH_SIZE);
+            Mesh::new_rectangle(ctx, DrawMode::fill(), rect, color.color_tuple().into())?
+                .draw(ctx, DrawParam::default())?;
+            if i == self.color {
+                let outline = Rect::new(10., y - 4., SWATCH_SIZE + 8., SWATCH_SIZE + 8.);
+                Mesh::new_rectangle(ctx, DrawMode::stroke(2.), outline, colors::WHITE.into())?
+                    .draw(ctx, DrawParam::default())?;
+            }
+        }
+
+        BetterText::new(
+            &format!("queue: {}", shapes_text(&self.position.queue)),
+            font,
+        )
+        .align(Align::TL(8., 8.))
+        .size(12)
+        .color(colors::LGREY)
+        .draw(ctx)?;
+        if let Some(message) = &self.message {
+            BetterText::new(message, font)
+                .align(Align::BC(0., 26.))
+                .size(10)
+                .color(colors::LGREY)
+                .draw(ctx)?;
+        }
+        BetterText::new(
+            "Space: paint  Tab: color  TLJSZOI: queue  H: hold  B: size  C/V: copy/paste  E: export  Return: play",
+            font,
+        )
+        .align(Align::BC(0., 8.))
+        .size(10)
+        .color(colors::GREY)
+        .draw(ctx)?;
+        Ok(())
+    }
+}
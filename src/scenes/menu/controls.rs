@@ -0,0 +1,100 @@
+use super::settings::{SettingsItem, SettingsState};
+use super::MenuScreen;
+use crate::controls::{Action, Controls, Trigger};
+use crate::util::{colors, Align, BetterText};
+use ggez::{event::KeyCode, graphics::Font, Context, GameResult};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ControlsState {
+    selected: usize,
+    waiting: bool, // for the key or button to bind
+}
+
+impl ControlsState {
+    pub fn new() -> ControlsState {
+        ControlsState {
+            selected: 0,
+            waiting: false,
+        }
+    }
+
+    fn selected_action(&self) -> Action {
+        Action::all()[self.selected]
+    }
+
+    pub fn pressed(&mut self, trigger: Trigger, controls: &mut Controls) -> Option<MenuScreen> {
+        if self.waiting {
+            self.waiting = false;
+            if trigger != Trigger::Key(KeyCode::Escape) {
+                controls.bind(self.selected_action(), trigger);
+            }
+            return None;
+        }
+        let actions = Action::all().len();
+        use KeyCode::*;
+        match trigger.menu_key()? {
+            Return | Space => self.waiting = true,
+            Delete | Back => controls.clear(self.selected_action()),
+            Up => self.selected = (self.selected + actions - 1) % actions,
+            Down => self.selected = (self.selected + 1) % actions,
+            Escape => {
+                return Some(MenuScreen::Settings(SettingsState::new(
+                    SettingsItem::Controls,
+                )))
+            }
+            _ => {}
+        }
+        None
+    }
+
+    pub fn draw(&self, ctx: &mut Context, font: Font, controls: &Controls) -> GameResult {
+        BetterText::new("Controls", font)
+            .align(Align::TL(50., 30.))
+            .color(colors::WHITE)
+            .draw(ctx)?;
+
+        for (i, action) in Action::all().into_iter().enumerate() {
+            let y = 70. + i as f32 * 22.;
+            let selected = i == self.selected;
+            let color = if selected {
+                colors::WHITE
+            } else {
+                colors::GREY
+            };
+            let name = if selected {
+                format!("> {}", action.name())
+            } else {
+                action.name().to_string()
+            };
+            BetterText::new(&name, font)
+                .align(Align::TL(50., y))
+                .size(12)
+                .color(color)
+                .draw(ctx)?;
+
+            let bound = if selected && self.waiting {
+                "press a key or button...".to_string()
+            } else {
+                controls
+                    .binding(action)
+                    .map(|b| {
+                        let names: Vec<String> = b.triggers().iter().map(|t| t.name()).collect();
+                        names.join(", ")
+                    })
+                    .unwrap_or_default()
+            };
+            BetterText::new(&bound, font)
+                .align(Align::TL(210., y))
+                .size(12)
+                .color(color)
+                .draw(ctx)?;
+        }
+
+        BetterText::new("Return: bind  Delete: clear  Esc: back", font)
+            .align(Align::BC(0., 30.))
+            .size(12)
+            .color(colors::LGREY)
+            .draw(ctx)?;
+        Ok(())
+    }
+}
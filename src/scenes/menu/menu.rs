@@ -1,6 +1,17 @@
+use super::controls::ControlsState;
 use super::online::*;
+use super::puzzles::PuzzleListState;
+use super::replays::ReplayListState;
+use super::settings::{SettingsItem, SettingsState};
+use crate::controls::Trigger;
+use crate::engine::Position;
+use crate::engine::{level::GameMode, EngineConfig};
+use crate::field::BoardSize;
 use crate::game::GameState;
+use crate::piece::RotationSystem;
+use crate::scenes::editor::EditorState;
 use crate::scenes::playing::PlayingState;
+use crate::settings::Settings;
 use crate::util::colors;
 use crate::util::{Align, BetterText};
 
@@ -10,43 +21,150 @@ use ggez::{Context, GameResult};
 
 #[derive(Clone, Debug)]
 pub enum MenuScreen {
-    Main(MainMenuItem),
+    Main(MainMenuItem, EngineConfig),
     OnlineInput(OnlineInputState),
+    Puzzles(PuzzleListState),
+    Replays(ReplayListState),
+    Settings(SettingsState),
+    Controls(ControlsState),
 }
 
 impl MenuScreen {
     pub fn default() -> MenuScreen {
-        MenuScreen::Main(MainMenuItem::Play)
+        MenuScreen::Main(MainMenuItem::Play, EngineConfig::new())
     }
     pub fn online() -> MenuScreen {
         MenuScreen::OnlineInput(OnlineInputState::new())
     }
 
-    pub fn draw(&mut self, ctx: &mut Context, font: Font) -> GameResult<()> {
+    pub fn draw(&mut self, ctx: &mut Context, font: Font, settings: &Settings) -> GameResult<()> {
         clear(ctx, colors::BG.into());
         use MenuScreen::*;
         match self {
-            Main(selected) => {
+            Main(selected, config) => {
                 let mut play = BetterText::new("Play", font)
                     .align(Align::TL(50., 50.))
                     .color(colors::GREY);
+                let mut mode = BetterText::new("", font)
+                    .align(Align::TL(70., 80.))
+                    .size(14)
+                    .color(colors::GREY);
+                let mut randomizer = BetterText::new("", font)
+                    .align(Align::TL(70., 105.))
+                    .size(14)
+                    .color(colors::GREY);
+                let mut piece_set = BetterText::new("", font)
+                    .align(Align::TL(70., 130.))
+                    .size(14)
+                    .color(colors::GREY);
+                let mut rotation = BetterText::new("", font)
+                    .align(Align::TL(70., 155.))
+                    .size(14)
+                    .color(colors::GREY);
+                let mut lock_down = BetterText::new("", font)
+                    .align(Align::TL(70., 180.))
+                    .size(14)
+                    .color(colors::GREY);
+                let mut board = BetterText::new("", font)
+                    .align(Align::TL(70., 205.))
+                    .size(14)
+                    .color(colors::GREY);
+                let mut messiness = BetterText::new("", font)
+                    .align(Align::TL(70., 230.))
+                    .size(14)
+                    .color(colors::GREY);
+                let mut puzzles = BetterText::new("Puzzles", font)
+                    .align(Align::TL(50., 275.))
+                    .color(colors::GREY);
+                let mut editor = BetterText::new("Board Editor", font)
+                    .align(Align::TL(50., 315.))
+                    .color(colors::GREY);
                 let mut play_online = BetterText::new("Play Online", font)
-                    .align(Align::TL(50., 100.))
+                    .align(Align::TL(50., 355.))
+                    .color(colors::GREY);
+                let mut replays = BetterText::new("Replays", font)
+                    .align(Align::TL(50., 395.))
+                    .color(colors::GREY);
+                let mut settings_text = BetterText::new("Settings", font)
+                    .align(Align::TL(50., 435.))
                     .color(colors::GREY);
                 let mut exit = BetterText::new("Exit", font)
                     .align(Align::BL(50., 50.))
                     .color(colors::GREY);
+                let mode_str = format!("Mode: {}", config.mode.name());
+                let randomizer_str = format!("Randomizer: {}", config.randomizer.name());
+                let piece_set_str = format!("Pieces: {}", config.piece_set.name);
+                let rotation_str = format!("Rotation: {}", config.rotation_system.name());
+                let lock_down_str = format!("Lock Down: {}", config.lock_down.name());
+                let board_str = format!("Board: {}", config.board.name());
+                let messiness_str =
+                    format!("Dig Messiness: {}%", (config.dig_messiness * 100.).round());
+                mode = mode.text(&mode_str);
+                messiness = messiness.text(&messiness_str);
+                randomizer = randomizer.text(&randomizer_str);
+                piece_set = piece_set.text(&piece_set_str);
+                rotation = rotation.text(&rotation_str);
+                lock_down = lock_down.text(&lock_down_str);
+                board = board.text(&board_str);
 
                 use MainMenuItem::*;
                 match selected {
                     Play => play = play.color(colors::WHITE).text("> Play!"),
+                    Mode => mode = mode.color(colors::WHITE).text(&format!("< {} >", mode_str)),
+                    Randomizer => {
+                        randomizer = randomizer
+                            .color(colors::WHITE)
+                            .text(&format!("< {} >", randomizer_str))
+                    }
+                    PieceSet => {
+                        piece_set = piece_set
+                            .color(colors::WHITE)
+                            .text(&format!("< {} >", piece_set_str))
+                    }
+                    Rotation => {
+                        rotation = rotation
+                            .color(colors::WHITE)
+                            .text(&format!("< {} >", rotation_str))
+                    }
+                    LockDown => {
+                        lock_down = lock_down
+                            .color(colors::WHITE)
+                            .text(&format!("< {} >", lock_down_str))
+                    }
+                    Board => {
+                        board = board
+                            .color(colors::WHITE)
+                            .text(&format!("< {} >", board_str))
+                    }
+                    Messiness => {
+                        messiness = messiness
+                            .color(colors::WHITE)
+                            .text(&format!("< {} >", messiness_str))
+                    }
+                    Puzzles => puzzles = puzzles.color(colors::WHITE).text("> Puzzles"),
+                    Editor => editor = editor.color(colors::WHITE).text("> Board Editor"),
                     PlayOnline => {
                         play_online = play_online.color(colors::LBLUE).text("> Play Online!")
                     }
+                    Replays => replays = replays.color(colors::WHITE).text("> Replays"),
+                    Settings => {
+                        settings_text = settings_text.color(colors::WHITE).text("> Settings")
+                    }
                     Exit => exit = exit.color(colors::LRED).text("> Exit"),
                 }
                 play.draw(ctx)?;
+                mode.draw(ctx)?;
+                randomizer.draw(ctx)?;
+                piece_set.draw(ctx)?;
+                rotation.draw(ctx)?;
+                lock_down.draw(ctx)?;
+                board.draw(ctx)?;
+                messiness.draw(ctx)?;
+                puzzles.draw(ctx)?;
+                editor.draw(ctx)?;
                 play_online.draw(ctx)?;
+                replays.draw(ctx)?;
+                settings_text.draw(ctx)?;
                 exit.draw(ctx)?;
             }
             OnlineInput(ref mut state) => {
@@ -56,40 +174,90 @@ impl MenuScreen {
                 // draw_queued_text(ctx, param(0., 0.), None, FilterMode::Linear)?;
                 // }
             }
+            Puzzles(ref state) => state.draw(ctx, font)?,
+            Replays(ref state) => state.draw(ctx, font)?,
+            Settings(ref state) => state.draw(ctx, font, settings)?,
+            Controls(ref state) => state.draw(ctx, font, &settings.controls)?,
         }
         Ok(())
     }
 
-    pub fn update(&mut self, ctx: &mut Context) -> Option<GameState> {
+    pub fn update(&mut self, ctx: &mut Context, settings: &Settings) -> Option<GameState> {
         use MenuScreen::*;
         if let OnlineInput(ref mut state) = self {
             // if let Some(new_state) = state.update(ctx) {
             //     return new_state;
             // }
-            return state.update(ctx);
+            return state.update(ctx, settings);
         }
         None
     }
 
-    pub fn pressed(&mut self, ctx: &mut Context, key: KeyCode) -> Option<GameState> {
+    pub fn pressed(
+        &mut self,
+        ctx: &mut Context,
+        trigger: Trigger,
+        settings: &mut Settings,
+    ) -> Option<GameState> {
         use KeyCode::*;
         use MenuScreen::*;
+        if let Controls(ref mut state) = self {
+            if let Some(new_state) = state.pressed(trigger, &mut settings.controls) {
+                *self = new_state;
+            }
+            return None;
+        }
+        let key = trigger.menu_key()?;
         match *self {
-            Main(ref mut selected) => {
+            Main(ref mut selected, ref mut config) => {
                 use MainMenuItem::*;
                 match key {
                     Return | Space => match selected {
-                        Play => Some(GameState::Playing(PlayingState::new(ctx, false))),
+                        Play | Mode | Randomizer | PieceSet | Rotation | LockDown | Board
+                        | Messiness => Some(GameState::Playing(PlayingState::with_config(
+                            ctx,
+                            false,
+                            config.reseeded(),
+                            settings,
+                        ))),
+                        Puzzles => {
+                            *self = MenuScreen::Puzzles(PuzzleListState::new(ctx));
+                            None
+                        }
+                        Editor => Some(GameState::Editor(EditorState::new(
+                            ctx,
+                            Position::new(),
+                            settings,
+                        ))),
                         PlayOnline => {
                             *self = MenuScreen::online();
                             None
                         }
+                        Replays => {
+                            *self = MenuScreen::Replays(ReplayListState::new());
+                            None
+                        }
+                        Settings => {
+                            *self = MenuScreen::Settings(SettingsState::new(SettingsItem::Volume));
+                            None
+                        }
                         Exit => Some(GameState::Exiting),
                     },
                     Down => {
                         *selected = match selected {
-                            Play => PlayOnline,
-                            PlayOnline => Exit,
+                            Play => Mode,
+                            Mode => Randomizer,
+                            Randomizer => PieceSet,
+                            PieceSet => Rotation,
+                            Rotation => LockDown,
+                            LockDown => Board,
+                            Board => Messiness,
+                            Messiness => Puzzles,
+                            Puzzles => Editor,
+                            Editor => PlayOnline,
+                            PlayOnline => Replays,
+                            Replays => Settings,
+                            Settings => Exit,
                             Exit => Play,
                         };
                         None
@@ -97,11 +265,61 @@ impl MenuScreen {
                     Up => {
                         *selected = match selected {
                             Play => Exit,
-                            PlayOnline => Play,
-                            Exit => PlayOnline,
+                            Mode => Play,
+                            Randomizer => Mode,
+                            Rotation => PieceSet,
+                            PieceSet => Randomizer,
+                            Messiness => Board,
+                            Board => LockDown,
+                            LockDown => Rotation,
+                            Puzzles => Messiness,
+                            Editor => Puzzles,
+                            PlayOnline => Editor,
+                            Replays => PlayOnline,
+                            Settings => Replays,
+                            Exit => Settings,
                         };
                         None
                     }
+                    Left | Right => {
+                        let forward = key == Right;
+                        match selected {
+                            Mode => config.mode = cycle(&GameMode::all(), config.mode, forward),
+                            Randomizer => {
+                                config.randomizer = cycle(
+                                    &crate::engine::Randomizer::all(),
+                                    config.randomizer,
+                                    forward,
+                                )
+                            }
+                            PieceSet => {
+                                // sets aren't Copy, so cycle through their names
+                                let sets = crate::piece::PieceSet::available();
+                                let names: Vec<&str> =
+                                    sets.iter().map(|s| s.name.as_str()).collect();
+                                let name = cycle(&names, config.piece_set.name.as_str(), forward);
+                                if let Some(set) = sets.iter().find(|s| s.name == name) {
+                                    config.piece_set = set.clone();
+                                }
+                            }
+                            Rotation => {
+                                config.rotation_system =
+                                    cycle(&RotationSystem::all(), config.rotation_system, forward)
+                            }
+                            LockDown => {
+                                config.lock_down =
+                                    cycle(&crate::util::LockDown::all(), config.lock_down, forward)
+                            }
+                            Board => config.board = cycle(&BoardSize::all(), config.board, forward),
+                            Messiness => {
+                                let step = if forward { 0.1 } else { -0.1 };
+                                let messiness = ((config.dig_messiness + step) * 10.).round() / 10.;
+                                config.dig_messiness = messiness.max(0.).min(1.);
+                            }
+                            _ => {}
+                        }
+                        None
+                    }
                     Escape => Some(GameState::Exiting),
                     _ => None,
                 }
@@ -112,6 +330,15 @@ impl MenuScreen {
                 }
                 None
             }
+            Puzzles(ref mut state) => state.pressed(ctx, key, settings),
+            Replays(ref mut state) => state.pressed(ctx, key, settings),
+            Settings(ref mut state) => {
+                if let Some(new_state) = state.pressed(ctx, key, settings) {
+                    *self = new_state;
+                }
+                None
+            }
+            Controls(_) => None,
         }
     }
 }
@@ -119,6 +346,28 @@ impl MenuScreen {
 #[derive(Clone, Debug, PartialEq)]
 pub enum MainMenuItem {
     Play,
+    Mode,
+    Randomizer,
+    PieceSet,
+    Rotation,
+    LockDown,
+    Board,
+    Messiness,
+    Puzzles,
+    Editor,
     PlayOnline,
+    Replays,
+    Settings,
     Exit,
 }
+
+/// Returns the item before or after `current` in `all`, wrapping around
+pub fn cycle<T: PartialEq + Copy>(all: &[T], current: T, forward: bool) -> T {
+    let i = all.iter().position(|t| *t == current).unwrap_or(0);
+    let i = if forward {
+        (i + 1) % all.len()
+    } else {
+        (i + all.len() - 1) % all.len()
+    };
+    all[i]
+}
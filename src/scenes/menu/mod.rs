@@ -1,4 +1,8 @@
+pub mod controls;
 mod menu;
 pub mod online;
+pub mod puzzles;
+pub mod replays;
+pub mod settings;
 
 pub use menu::*;
@@ -1,128 +1,336 @@
 use super::{MainMenuItem, MenuScreen};
-use crate::net::Netinfo;
-use crate::util::{colors, Align, BetterText};
+use crate::engine::EngineConfig;
+use crate::net::{Connecting, NetConfig, Progress, RecentPeer, RecentPeers, Role, DEFAULT_PORT};
+use crate::settings::Settings;
+use crate::util::{colors, Align, BetterText, TextInput};
 use crate::GameState;
-use ggez::{event::KeyCode, graphics::Font, Context, GameResult};
+use ggez::input::keyboard::{self, KeyMods};
+use ggez::{event::KeyCode, graphics::Font, timer, Context, GameResult};
+use std::net::{IpAddr, SocketAddr};
 
-#[derive(Clone, Debug, PartialEq)]
+/// Seconds between the dots of "connecting..." growing
+const CONNECTING_DOT_TIME: f64 = 0.4;
+/// Long enough for any host name
+const ADDRESS_LEN_MAX: usize = 253;
+
+#[derive(Clone, Debug)]
 pub struct OnlineInputState {
     pub connection_state: ConnectionState,
-    pub selected_digit: usize,
-    pub peer_ip: [u8; 12],
-    // error: Option<(f64, String)>,
+    selected: OnlineItem,
+    host: bool,
+    /// Name or address of the host to join, optionally with a port
+    address: TextInput,
+    port: TextInput,
+    bind: TextInput,
+    recent: RecentPeers,
+    /// Which recent peer was last filled in
+    recent_index: Option<usize>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum OnlineItem {
+    Role,
+    Recent,
+    Address,
+    Port,
+    Bind,
 }
 
 impl OnlineInputState {
     pub fn new() -> OnlineInputState {
+        let recent = RecentPeers::load();
+        let (address, port) = match recent.peers.first() {
+            Some(peer) => (peer.address.clone(), peer.port),
+            None => ("127.0.0.1".to_string(), DEFAULT_PORT),
+        };
         OnlineInputState {
             connection_state: ConnectionState::Idle,
-            peer_ip: [1, 2, 7, 0, 0, 0, 0, 0, 0, 0, 0, 1],
-            selected_digit: 0,
-            // error: None,
+            selected: OnlineItem::Role,
+            host: false,
+            address: TextInput::new(&address, ADDRESS_LEN_MAX),
+            port: TextInput::new(&port.to_string(), 5),
+            bind: TextInput::new("0.0.0.0", ADDRESS_LEN_MAX),
+            recent_index: recent.peers.first().map(|_| 0),
+            recent,
         }
     }
-    pub fn peer_ip_str(&self) -> String {
-        let mut s = String::new();
-        for (i, d) in self.peer_ip.iter().enumerate() {
-            s += format!("{}", d).as_str();
-            if i != self.peer_ip.len() - 1 && (i + 1) % 3 == 0 {
-                s += ".";
-            }
+
+    /// The rows shown for the current role, top to bottom
+    fn items(&self) -> Vec<OnlineItem> {
+        use OnlineItem::*;
+        if self.host {
+            vec![Role, Port, Bind]
+        } else if self.recent.peers.is_empty() {
+            vec![Role, Address, Port, Bind]
+        } else {
+            vec![Role, Recent, Address, Port, Bind]
         }
-        s
     }
 
-    pub fn update(&mut self, ctx: &mut Context) -> Option<GameState> {
-        if let ConnectionState::Waiting(true) = self.connection_state {
-            match Netinfo::new(self.peer_ip_str()) {
-                Ok(n) => {
-                    self.connection_state = ConnectionState::Connecting;
-                    return Some(GameState::playing_online(ctx, None, n));
+    /// Returns what's wrong with the text of a row, if anything
+    fn problem(&self, item: OnlineItem) -> Option<String> {
+        match item {
+            OnlineItem::Role | OnlineItem::Recent => None,
+            OnlineItem::Address => self.host_and_port().err(),
+            OnlineItem::Port => self.port().err(),
+            OnlineItem::Bind => {
+                let bind = self.bind.text();
+                match bind.parse::<IpAddr>() {
+                    Ok(_) => None,
+                    Err(_) => Some(format!("'{}' is not an IP address", bind)),
                 }
-                Err(err) => {
-                    println!("Netinfo error:\n{}", err);
-                    // self.error = Some((0., err));
-                    self.connection_state = ConnectionState::Failed(err);
+            }
+        }
+    }
+
+    fn port(&self) -> Result<u16, String> {
+        let port = self.port.text();
+        match port.parse::<u16>() {
+            Ok(port) if port > 0 => Ok(port),
+            _ => Err(format!("'{}' is not a port", port)),
+        }
+    }
+
+    /// Reads the address row. It may bring its own port, as in `host:port` or `[::1]:port`.
+    fn host_and_port(&self) -> Result<(String, Option<u16>), String> {
+        let address = self.address.text();
+        if address.is_empty() {
+            return Err("Enter the host to join".to_string());
+        }
+        if let Ok(addr) = address.parse::<SocketAddr>() {
+            return Ok((addr.ip().to_string(), Some(addr.port())));
+        }
+        if address.parse::<IpAddr>().is_ok() {
+            return Ok((address, None));
+        }
+        let (host, port) = match address.rfind(':') {
+            Some(i) => {
+                let port = address[i + 1..]
+                    .parse::<u16>()
+                    .map_err(|_| format!("'{}' has no valid port", address))?;
+                (address[..i].to_string(), Some(port))
+            }
+            None => (address.clone(), None),
+        };
+        let valid = |c: char| c.is_ascii_alphanumeric() || c == '.' || c == '-';
+        if host.is_empty() || !host.chars().all(valid) {
+            return Err(format!("'{}' is not a host name or address", host));
+        }
+        Ok((host, port))
+    }
+
+    fn net_config(&self) -> Result<NetConfig, String> {
+        let items = self.items();
+        if let Some(problem) = items.iter().find_map(|item| self.problem(*item)) {
+            return Err(problem);
+        }
+        let (role, port) = if self.host {
+            (Role::Host, self.port()?)
+        } else {
+            let (host, port) = self.host_and_port()?;
+            (Role::Join(host), port.map_or_else(|| self.port(), Ok)?)
+        };
+        Ok(NetConfig {
+            role,
+            bind: self.bind.text(),
+            port,
+        })
+    }
+
+    pub fn update(&mut self, ctx: &mut Context, settings: &Settings) -> Option<GameState> {
+        let mut outcome = None;
+        if let ConnectionState::Connecting(connecting, status, time) = &mut self.connection_state {
+            *time += timer::delta(ctx).as_secs_f64();
+            while let Some(progress) = connecting.poll() {
+                match progress {
+                    Progress::Status(text) => *status = text,
+                    done => {
+                        outcome = Some(done);
+                        break;
+                    }
                 }
             }
         }
-        None
+        match outcome? {
+            Progress::Connected(n) => {
+                self.connection_state = ConnectionState::Idle;
+                self.remember_peer();
+                Some(GameState::playing_online(ctx, None, n, settings))
+            }
+            Progress::Failed(err) => {
+                println!("Netinfo error:\n{}", err);
+                self.connection_state = ConnectionState::Failed(err);
+                None
+            }
+            Progress::Status(_) => None,
+        }
+    }
+
+    /// Puts the joined host at the front of the recent peers
+    fn remember_peer(&mut self) {
+        if let Ok(NetConfig {
+            role: Role::Join(address),
+            port,
+            ..
+        }) = self.net_config()
+        {
+            self.recent.add(RecentPeer { address, port });
+            if let Err(err) = self.recent.save() {
+                println!("Could not save recent peers:\n{}", err);
+            }
+        }
+    }
+
+    /// Takes a character from ggez's text input into the selected row
+    pub fn typed(&mut self, c: char) {
+        if let ConnectionState::Connecting(..) = self.connection_state {
+            return;
+        }
+        if let Some(input) = self.selected_input() {
+            input.typed(c);
+        }
     }
 
-    pub fn pressed(&mut self, _ctx: &mut Context, key: KeyCode) -> Option<MenuScreen> {
+    pub fn pressed(&mut self, ctx: &mut Context, key: KeyCode) -> Option<MenuScreen> {
         use KeyCode::*;
+        let items = self.items();
+        let i = items.iter().position(|i| *i == self.selected).unwrap_or(0);
+        if let ConnectionState::Connecting(connecting, ..) = &self.connection_state {
+            if key == Escape {
+                connecting.cancel();
+                self.connection_state = ConnectionState::Idle;
+            }
+            return None;
+        }
         match key {
             Return => {
-                // if valid_ip()
-                self.connection_state = ConnectionState::Waiting(false);
+                self.connection_state = match self.net_config() {
+                    Ok(config) => {
+                        ConnectionState::Connecting(Connecting::start(config), String::new(), 0.)
+                    }
+                    Err(err) => ConnectionState::Failed(err),
+                };
             }
-            Up => {
-                if self.peer_ip[self.selected_digit] < 9 {
-                    self.peer_ip[self.selected_digit] += 1;
+            Up => self.selected = items[(i + items.len() - 1) % items.len()],
+            Down => self.selected = items[(i + 1) % items.len()],
+            Left | Right if self.selected == OnlineItem::Role => {
+                self.host = !self.host;
+                if !self.items().contains(&self.selected) {
+                    self.selected = OnlineItem::Role;
                 }
             }
-            Down => {
-                if let Some(d) = self.peer_ip[self.selected_digit].checked_sub(1) {
-                    self.peer_ip[self.selected_digit] = d;
-                }
+            Left | Right if self.selected == OnlineItem::Recent => {
+                let len = self.recent.peers.len();
+                let i = match (self.recent_index, key == Right) {
+                    (None, _) => 0,
+                    (Some(i), true) => (i + 1) % len,
+                    (Some(i), false) => (i + len - 1) % len,
+                };
+                let peer = &self.recent.peers[i];
+                self.address.set_text(&peer.address);
+                self.port.set_text(&peer.port.to_string());
+                self.recent_index = Some(i);
             }
-            Left => {
-                if let Some(d) = self.selected_digit.checked_sub(1) {
-                    self.selected_digit = d;
-                }
+            Escape => {
+                return Some(MenuScreen::Main(
+                    MainMenuItem::PlayOnline,
+                    EngineConfig::new(),
+                ));
             }
-            Right => {
-                if self.selected_digit < self.peer_ip.len() {
-                    self.selected_digit += 1;
+            V if keyboard::is_mod_active(ctx, KeyMods::CTRL) => {
+                if let Some(input) = self.selected_input() {
+                    if let Err(err) = input.paste() {
+                        println!("Could not paste:\n{}", err);
+                    }
                 }
             }
-            Escape => {
-                return Some(MenuScreen::Main(MainMenuItem::PlayOnline));
+            _ => {
+                if let Some(input) = self.selected_input() {
+                    input.pressed(key);
+                }
             }
-            _ => {}
         }
         None
     }
 
+    fn selected_input(&mut self) -> Option<&mut TextInput> {
+        match self.selected {
+            OnlineItem::Role | OnlineItem::Recent => None,
+            OnlineItem::Address => Some(&mut self.address),
+            OnlineItem::Port => Some(&mut self.port),
+            OnlineItem::Bind => Some(&mut self.bind),
+        }
+    }
+
     pub fn draw(&mut self, ctx: &mut Context, font: Font) -> GameResult {
-        BetterText::new("Peer ip adress:", font)
+        BetterText::new("Play Online", font)
             .align(Align::TL(50., 50.))
             .color(colors::WHITE)
             .draw(ctx)?;
-        // txt(ctx, "Peer ip adress:", 50., 100., colors::WHITE.into());
-        // draw_queued_text(ctx, param(0., 0.), None, FilterMode::Linear)?;
-        for (i, digit) in self.peer_ip.iter().enumerate() {
-            let (mut txt_str, mut offset) = if i == self.selected_digit {
-                (format!("({})", digit), -3.5)
-            } else {
-                (digit.to_string(), 0.)
+        let items = self.items();
+        // the problem of the selected row goes below the last one
+        let problem_y = 90. + items.len() as f32 * 25.;
+        for (i, item) in items.into_iter().enumerate() {
+            let selected = item == self.selected;
+            let input_text = |input: &TextInput| {
+                if selected {
+                    input.with_caret()
+                } else {
+                    input.text()
+                }
             };
-            if (i + 1) % 3 == 0 && i < self.peer_ip.len() - 1 {
-                txt_str += ".";
-            }
-            offset += (i as f32 / 3.).floor() * 18.;
-
-            BetterText::new(&txt_str, font)
-                .align(Align::TL(50. + i as f32 * 18. + offset, 70.))
+            let text = match item {
+                OnlineItem::Role => format!("Role: {}", if self.host { "Host" } else { "Join" }),
+                OnlineItem::Recent => {
+                    let peer = self.recent.peers.get(self.recent_index.unwrap_or(0));
+                    format!("Recent: {}", peer.map_or(String::new(), RecentPeer::name))
+                }
+                OnlineItem::Address => format!("Host: {}", input_text(&self.address)),
+                OnlineItem::Port => format!("Port: {}", input_text(&self.port)),
+                OnlineItem::Bind => format!("Bind Address: {}", input_text(&self.bind)),
+            };
+            let problem = self.problem(item);
+            let text = match item {
+                OnlineItem::Role | OnlineItem::Recent if selected => format!("< {} >", text),
+                _ => text,
+            };
+            let color = match (selected, problem.is_some()) {
+                (_, true) => colors::LRED,
+                (true, false) => colors::WHITE,
+                (false, false) => colors::GREY,
+            };
+            BetterText::new(&text, font)
+                .align(Align::TL(70., 90. + i as f32 * 25.))
                 .size(16)
-                .color(colors::OFF_WHITE)
+                .color(color)
                 .draw(ctx)?;
+            if let (true, Some(problem)) = (selected, problem) {
+                BetterText::new(&problem, font)
+                    .align(Align::TL(70., problem_y))
+                    .size(12)
+                    .color(colors::LRED)
+                    .draw(ctx)?;
+            }
         }
 
-        BetterText::new("Use Arrow Keys to set address:", font)
+        BetterText::new("Type to edit, Ctrl+V to paste, Return to connect", font)
             .align(Align::BC(0., 50.))
             .size(16)
             .color(colors::LGREY)
             .draw(ctx)?;
 
-        if let ConnectionState::Waiting(ref mut b) = self.connection_state {
-            // println!("called draw with busy connection");
-            BetterText::new("connecting...", font)
+        if let ConnectionState::Connecting(_, status, time) = &self.connection_state {
+            let dots = ".".repeat((*time / CONNECTING_DOT_TIME) as usize % 4);
+            BetterText::new(&format!("connecting{}", dots), font)
                 .align(Align::BC(0., 110.))
                 .size(20)
                 .color(colors::LGREY)
                 .draw(ctx)?;
-            *b = true;
+            BetterText::new(&format!("{}  (Escape to cancel)", status), font)
+                .align(Align::BC(0., 90.))
+                .size(14)
+                .color(colors::GREY)
+                .draw(ctx)?;
         }
 
         if let ConnectionState::Failed(err) = &self.connection_state {
@@ -141,11 +349,11 @@ impl OnlineInputState {
     }
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug)]
 pub enum ConnectionState {
     Idle,
-    Waiting(bool), // true when text has been drawn
-    Connecting,
+    /// The worker is at it, with its latest status and the seconds since it started
+    Connecting(Connecting, String, f64),
     Failed(String),
 }
 
@@ -0,0 +1,115 @@
+use super::{MainMenuItem, MenuScreen};
+use crate::engine::{shapes_text, EngineConfig};
+use crate::game::GameState;
+use crate::puzzles::Puzzle;
+use crate::records::Records;
+use crate::scenes::playing::PlayingState;
+use crate::settings::Settings;
+use crate::util::{colors, Align, BetterText};
+use ggez::{event::KeyCode, graphics::Font, Context, GameResult};
+
+const VISIBLE_ROWS: usize = 12;
+
+#[derive(Clone, Debug)]
+pub struct PuzzleListState {
+    puzzles: Vec<Puzzle>,
+    solved: Vec<bool>,
+    selected: usize,
+    error: Option<String>,
+}
+
+impl PuzzleListState {
+    pub fn new(ctx: &mut Context) -> PuzzleListState {
+        let (puzzles, mut error) = match Puzzle::bundled(ctx) {
+            Ok(puzzles) => (puzzles, None),
+            Err(err) => (vec![], Some(err)),
+        };
+        let records = Records::load().unwrap_or_else(|err| {
+            error = error.take().or(Some(err));
+            Records::default()
+        });
+        let solved = puzzles.iter().map(|p| records.is_solved(&p.id)).collect();
+        PuzzleListState {
+            puzzles,
+            solved,
+            selected: 0,
+            error,
+        }
+    }
+
+    pub fn pressed(
+        &mut self,
+        ctx: &mut Context,
+        key: KeyCode,
+        settings: &Settings,
+    ) -> Option<GameState> {
+        use KeyCode::*;
+        let len = self.puzzles.len().max(1);
+        match key {
+            Up => self.selected = (self.selected + len - 1) % len,
+            Down => self.selected = (self.selected + 1) % len,
+            Return | Space => {
+                if let Some(puzzle) = self.puzzles.get(self.selected) {
+                    let state = PlayingState::puzzle(ctx, puzzle, settings);
+                    return Some(GameState::Playing(state));
+                }
+            }
+            Escape => {
+                let main = MenuScreen::Main(MainMenuItem::Puzzles, EngineConfig::new());
+                return Some(GameState::Menu(main));
+            }
+            _ => {}
+        }
+        None
+    }
+
+    pub fn draw(&self, ctx: &mut Context, font: Font) -> GameResult {
+        let solved = self.solved.iter().filter(|s| **s).count();
+        BetterText::new(&format!("Puzzles  {}/{}", solved, self.puzzles.len()), font)
+            .align(Align::TL(50., 30.))
+            .color(colors::WHITE)
+            .draw(ctx)?;
+
+        let first = self.selected.saturating_sub(VISIBLE_ROWS - 1);
+        for (i, puzzle) in self
+            .puzzles
+            .iter()
+            .enumerate()
+            .skip(first)
+            .take(VISIBLE_ROWS)
+        {
+            let mark = if self.solved[i] { "[x]" } else { "[ ]" };
+            let (text, color) = if i == self.selected {
+                (format!("> {} {}", mark, puzzle.name), colors::WHITE)
+            } else {
+                (format!("{} {}", mark, puzzle.name), colors::GREY)
+            };
+            BetterText::new(&text, font)
+                .align(Align::TL(50., 70. + (i - first) as f32 * 22.))
+                .size(14)
+                .color(color)
+                .draw(ctx)?;
+        }
+
+        if let Some(puzzle) = self.puzzles.get(self.selected) {
+            let info = format!(
+                "{}  pieces {}",
+                puzzle.goal.name(),
+                shapes_text(&puzzle.position.queue)
+            );
+            BetterText::new(&info, font)
+                .align(Align::BC(0., 50.))
+                .size(12)
+                .color(colors::LGREY)
+                .draw(ctx)?;
+        }
+        if let Some(err) = &self.error {
+            BetterText::new(err, font)
+                .align(Align::BC(0., 30.))
+                .size(12)
+                .color(colors::LRED)
+                .draw(ctx)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,136 @@
+use super::{MainMenuItem, MenuScreen};
+use crate::engine::{EngineConfig, Replay, TICK_DELTA};
+use crate::game::GameState;
+use crate::scenes::replay::ReplayState;
+use crate::settings::Settings;
+use crate::util::{colors, format_time, Align, BetterText};
+use ggez::{event::KeyCode, graphics::Font, Context, GameResult};
+use std::path::PathBuf;
+
+const VISIBLE_ROWS: usize = 12;
+
+#[derive(Clone, Debug)]
+pub struct ReplayListState {
+    replays: Vec<PathBuf>,
+    selected: usize,
+    info: Option<String>, // about the selected replay
+    error: Option<String>,
+}
+
+impl ReplayListState {
+    pub fn new() -> ReplayListState {
+        let (replays, error) = match Replay::list() {
+            Ok(replays) => (replays, None),
+            Err(err) => (vec![], Some(err)),
+        };
+        let mut state = ReplayListState {
+            replays,
+            selected: 0,
+            info: None,
+            error,
+        };
+        state.select(0);
+        state
+    }
+
+    fn select(&mut self, i: usize) {
+        self.selected = i;
+        self.info = self
+            .replays
+            .get(i)
+            .and_then(|path| Replay::load(path).ok())
+            .map(|replay| {
+                format!(
+                    "{}  {}  seed {}",
+                    replay.config.mode.name(),
+                    format_time(replay.ticks as f64 * TICK_DELTA),
+                    replay.config.seed
+                )
+            });
+    }
+
+    pub fn pressed(
+        &mut self,
+        ctx: &mut Context,
+        key: KeyCode,
+        settings: &Settings,
+    ) -> Option<GameState> {
+        use KeyCode::*;
+        let len = self.replays.len().max(1);
+        match key {
+            Up => self.select((self.selected + len - 1) % len),
+            Down => self.select((self.selected + 1) % len),
+            Return | Space => {
+                if let Some(path) = self.replays.get(self.selected) {
+                    match Replay::load(path) {
+                        Ok(replay) => {
+                            let state = ReplayState::new(ctx, replay, settings);
+                            return Some(GameState::Replay(state));
+                        }
+                        Err(err) => self.error = Some(err),
+                    }
+                }
+            }
+            Escape => {
+                let main = MenuScreen::Main(MainMenuItem::Replays, EngineConfig::new());
+                return Some(GameState::Menu(main));
+            }
+            _ => {}
+        }
+        None
+    }
+
+    pub fn draw(&self, ctx: &mut Context, font: Font) -> GameResult {
+        BetterText::new("Replays", font)
+            .align(Align::TL(50., 30.))
+            .color(colors::WHITE)
+            .draw(ctx)?;
+
+        if self.replays.is_empty() {
+            BetterText::new("No replays yet, play a game first", font)
+                .align(Align::TL(50., 70.))
+                .size(14)
+                .color(colors::GREY)
+                .draw(ctx)?;
+        }
+        let first = self.selected.saturating_sub(VISIBLE_ROWS - 1);
+        for (i, path) in self
+            .replays
+            .iter()
+            .enumerate()
+            .skip(first)
+            .take(VISIBLE_ROWS)
+        {
+            let name = path
+                .file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let (text, color) = if i == self.selected {
+                (format!("> {}", name), colors::WHITE)
+            } else {
+                (name, colors::GREY)
+            };
+            BetterText::new(&text, font)
+                .align(Align::TL(50., 70. + (i - first) as f32 * 22.))
+                .size(14)
+                .color(color)
+                .draw(ctx)?;
+        }
+
+        if let Some(info) = &self.info {
+            BetterText::new(info, font)
+                .align(Align::BC(0., 50.))
+                .size(12)
+                .color(colors::LGREY)
+                .draw(ctx)?;
+        }
+        if let Some(err) = &self.error {
+            BetterText::new(err, font)
+                .align(Align::BC(0., 30.))
+                .size(12)
+                .color(colors::LRED)
+                .draw(ctx)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,234 @@
+use super::controls::ControlsState;
+use super::{MainMenuItem, MenuScreen};
+use crate::engine::EngineConfig;
+use crate::settings::{min_window_size, Settings};
+use crate::util::{colors, Align, BetterText};
+use ggez::{event::KeyCode, graphics, graphics::Font, Context, GameResult};
+
+const SOFT_DROP_FACTORS: [Option<f64>; 8] = [
+    Some(1.),
+    Some(2.),
+    Some(5.),
+    Some(10.),
+    Some(20.),
+    Some(40.),
+    Some(80.),
+    None,
+];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SettingsItem {
+    Volume,
+    PausedVolume,
+    BlockSize,
+    WindowSize,
+    Das,
+    Arr,
+    Dcd,
+    SoftDrop,
+    Controls,
+    Back,
+}
+
+impl SettingsItem {
+    fn all() -> Vec<SettingsItem> {
+        use SettingsItem::*;
+        vec![
+            Volume,
+            PausedVolume,
+            BlockSize,
+            WindowSize,
+            Das,
+            Arr,
+            Dcd,
+            SoftDrop,
+            Controls,
+            Back,
+        ]
+    }
+
+    fn name(self) -> &'static str {
+        use SettingsItem::*;
+        match self {
+            Volume => "Volume",
+            PausedVolume => "Paused Volume",
+            BlockSize => "Block Size",
+            WindowSize => "Window Size",
+            Das => "DAS",
+            Arr => "ARR",
+            Dcd => "DCD",
+            SoftDrop => "Soft Drop",
+            Controls => "Controls",
+            Back => "Back",
+        }
+    }
+
+    fn value(self, settings: &Settings) -> Option<String> {
+        use SettingsItem::*;
+        let handling = &settings.handling;
+        let millis = |secs: f64| format!("{}ms", (secs * 1000.).round());
+        match self {
+            Volume => Some(format!("{}%", (settings.volume * 100.).round())),
+            PausedVolume => Some(format!("{}%", (settings.paused_volume * 100.).round())),
+            BlockSize => Some(format!("{}px", settings.block_size)),
+            WindowSize => Some(format!(
+                "{}x{}",
+                settings.window_width.round(),
+                settings.window_height.round()
+            )),
+            Das => Some(millis(handling.das)),
+            Arr if handling.arr <= 0. => Some("instant".to_string()),
+            Arr => Some(millis(handling.arr)),
+            Dcd => Some(millis(handling.dcd)),
+            SoftDrop => Some(match handling.soft_drop_factor {
+                Some(factor) => format!("{}x", factor),
+                None => "instant".to_string(),
+            }),
+            Controls | Back => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SettingsState {
+    selected: SettingsItem,
+    error: Option<String>,
+}
+
+impl SettingsState {
+    pub fn new(selected: SettingsItem) -> SettingsState {
+        SettingsState {
+            selected,
+            error: None,
+        }
+    }
+
+    pub fn pressed(
+        &mut self,
+        ctx: &mut Context,
+        key: KeyCode,
+        settings: &mut Settings,
+    ) -> Option<MenuScreen> {
+        use KeyCode::*;
+        let items = SettingsItem::all();
+        let i = items.iter().position(|i| *i == self.selected).unwrap_or(0);
+        match key {
+            Up => self.selected = items[(i + items.len() - 1) % items.len()],
+            Down => self.selected = items[(i + 1) % items.len()],
+            Left | Right => {
+                self.change(settings, key == Right);
+                *settings = settings.clone().validated();
+            }
+            Return | Space => match self.selected {
+                SettingsItem::Controls => return Some(MenuScreen::Controls(ControlsState::new())),
+                SettingsItem::Back => return self.leave(ctx, settings),
+                _ => {}
+            },
+            Escape => return self.leave(ctx, settings),
+            _ => {}
+        }
+        None
+    }
+
+    fn change(&self, settings: &mut Settings, up: bool) {
+        use SettingsItem::*;
+        let sign = if up { 1. } else { -1. };
+        // steps of 10ms, rounded so they don't drift
+        let step_millis = |secs: f64| ((secs + sign as f64 * 0.01) * 100.).round() / 100.;
+        let handling = &mut settings.handling;
+        match self.selected {
+            Volume => settings.volume = ((settings.volume + sign * 0.1) * 10.).round() / 10.,
+            PausedVolume => {
+                settings.paused_volume = ((settings.paused_volume + sign * 0.1) * 10.).round() / 10.
+            }
+            BlockSize => settings.block_size += sign,
+            WindowSize => {
+                let (min_width, min_height) = min_window_size(settings.block_size);
+                settings.window_width += sign * min_width * 0.1;
+                settings.window_height += sign * min_height * 0.1;
+            }
+            Das => handling.das = step_millis(handling.das),
+            Arr => handling.arr = step_millis(handling.arr),
+            Dcd => handling.dcd = step_millis(handling.dcd),
+            SoftDrop => {
+                let i = SOFT_DROP_FACTORS
+                    .iter()
+                    .position(|f| *f == handling.soft_drop_factor)
+                    .unwrap_or(4);
+                let i = if up {
+                    (i + 1).min(SOFT_DROP_FACTORS.len() - 1)
+                } else {
+                    i.saturating_sub(1)
+                };
+                handling.soft_drop_factor = SOFT_DROP_FACTORS[i];
+            }
+            Controls | Back => {}
+        }
+    }
+
+    /// Saves and applies the settings and goes back to the main menu
+    fn leave(&mut self, ctx: &mut Context, settings: &Settings) -> Option<MenuScreen> {
+        let result = settings.save().and_then(|_| {
+            graphics::set_mode(ctx, settings.window_mode()).map_err(|e| e.to_string())
+        });
+        match result {
+            Ok(()) => Some(MenuScreen::Main(
+                MainMenuItem::Settings,
+                EngineConfig::new(),
+            )),
+            Err(err) => {
+                self.error = Some(err);
+                None
+            }
+        }
+    }
+
+    pub fn draw(&self, ctx: &mut Context, font: Font, settings: &Settings) -> GameResult {
+        BetterText::new("Settings", font)
+            .align(Align::TL(50., 30.))
+            .color(colors::WHITE)
+            .draw(ctx)?;
+
+        for (i, item) in SettingsItem::all().into_iter().enumerate() {
+            let y = 70. + i as f32 * 24.;
+            let selected = item == self.selected;
+            let color = if selected {
+                colors::WHITE
+            } else {
+                colors::GREY
+            };
+            let name = if selected {
+                format!("> {}", item.name())
+            } else {
+                item.name().to_string()
+            };
+            BetterText::new(&name, font)
+                .align(Align::TL(50., y))
+                .size(14)
+                .color(color)
+                .draw(ctx)?;
+
+            if let Some(value) = item.value(settings) {
+                let value = if selected {
+                    format!("< {} >", value)
+                } else {
+                    value
+                };
+                BetterText::new(&value, font)
+                    .align(Align::TL(250., y))
+                    .size(14)
+                    .color(color)
+                    .draw(ctx)?;
+            }
+        }
+
+        if let Some(err) = &self.error {
+            BetterText::new(&format!("Could not save: {}", err), font)
+                .align(Align::BC(0., 30.))
+                .size(12)
+                .color(colors::LRED)
+                .draw(ctx)?;
+        }
+        Ok(())
+    }
+}
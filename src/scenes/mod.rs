@@ -1,4 +1,6 @@
+pub mod editor;
 pub mod menu;
 pub mod paused;
 pub mod playing;
 pub mod playing_online;
+pub mod replay;
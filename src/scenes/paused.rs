@@ -62,10 +62,15 @@ impl PausedMenuState {
                 None
             }
             Return | Space => match self.selected {
-                Continue => Some(GameState::Playing(playing_state.clone())),
-                Exit => Some(GameState::default()),
+                Continue => Some(GameState::Playing(playing_state.resumed())),
+                Exit => {
+                    if let Err(err) = playing_state.save_replay() {
+                        println!("Could not save replay:\n{}", err);
+                    }
+                    Some(playing_state.exit_state())
+                }
             },
-            Escape => Some(GameState::Playing(playing_state.clone())),
+            Escape => Some(GameState::Playing(playing_state.resumed())),
             _ => None,
         }
     }
@@ -1,6 +1,4 @@
-pub mod level;
 mod playing;
 pub mod sidebar;
 
 pub use playing::*;
-pub use sidebar::SIDEBAR_WIDTH;
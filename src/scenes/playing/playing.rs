@@ -1,196 +1,155 @@
-use super::level::*;
 use super::sidebar;
-use crate::net::{self, Netinfo};
-use crate::piece::{rot_steps::RotSteps, *};
-use crate::scenes::{paused::PausedMenuState, playing_online::PlayingOnlineState};
-use crate::util::{colors, music, types::*, Align, BetterText, OnHoldState, RngPieceQueue};
-use crate::{block::*, field::*, game::GameState, get_win_dim};
-
-use ggez::{event::KeyCode, graphics::*, timer::delta, *};
-use rand::{thread_rng, Rng};
-
-// const TICK_DELTA: f64 = 0.6;
-const ADD_PIECE_DELAY: f64 = 0.11;
-const ADD_PIECE_DELAY_DESTROYED: f64 = 0.74;
-const DESTROY_DELTA: f64 = 0.3;
-const DESTROY_HIDE_DELTA: f64 = 0.12;
+use crate::controls::Action;
+use crate::engine::{level::*, Engine, EngineConfig, EngineEvent, InputRepeater, Replay};
+use crate::net::{self, MirrorBoard, Netinfo};
+use crate::puzzles::Puzzle;
+use crate::records::{Records, SprintRecord, UltraRecord};
+use crate::render::Renderer;
+use crate::scenes::editor::EditorState;
+use crate::scenes::paused::PausedMenuState;
+use crate::scenes::playing_online::{EnemyState, PlayingOnlineState};
+use crate::settings::Settings;
+use crate::util::{colors, format_time, music, Align, BetterText};
+use crate::{game::GameState, get_win_dim};
+
+use ggez::{graphics::*, timer::delta, *};
 
 #[derive(Clone, Debug)]
 pub struct PlayingState {
-    delta: f64,
     music: music::MusicInfo,
-    leveling: LevelingType, // level and time to next
-    field: PlayingField,
-    piece: PieceState,
-    next_pieces: RngPieceQueue,
-    hold_piece: Option<HoldPiece>,
-    score: usize,
-    on_hold: Option<OnHoldState>,
-    destroying_rows_indices: Option<(f64, Vec<usize>)>,
-    garbage_to_add: usize,
-    game_over: bool,
+    engine: Engine,
+    repeater: InputRepeater,
+    renderer: Renderer,
+    events: Vec<EngineEvent>,       // not yet sent to the peer
+    callout: Option<(f64, String)>, // time left, text
     online: bool,
+    replay_status: Option<String>,
+    keys: usize, // presses that moved the piece, not counting repeats
+    personal_best: Option<SprintRecord>,
+    new_best: bool,
+    /// The ultra leaderboard after this game was added, with its place on it
+    leaderboard: Vec<UltraRecord>,
+    place: Option<usize>,
+    puzzle: Option<String>, // id of the puzzle being played
+    /// The editor the board came from, to go back to when leaving
+    editor: Option<Box<EditorState>>,
 }
 
+const CALLOUT_DURATION: f64 = 1.5;
+
 impl PlayingState {
-    pub fn new(ctx: &mut Context, online: bool) -> PlayingState {
-        let next_pieces = RngPieceQueue::new(ctx).expect("Failed to generate piece queue");
-        let leveling = if online {
-            LevelingType::online()
-        } else {
-            LevelingType::single()
+    pub fn new(ctx: &mut Context, online: bool, settings: &Settings) -> PlayingState {
+        Self::with_config(ctx, online, EngineConfig::new(), settings)
+    }
+
+    pub fn with_config(
+        ctx: &mut Context,
+        online: bool,
+        config: EngineConfig,
+        settings: &Settings,
+    ) -> PlayingState {
+        let music = music::MusicInfo::new(ctx, settings.volume, settings.paused_volume)
+            .expect("Failed to load music fileds");
+        let renderer = Renderer::new(ctx, settings.block_size).expect("Failed to build meshes");
+        let personal_best = match config.mode {
+            GameMode::Sprint(lines) if !online => Records::load()
+                .ok()
+                .and_then(|records| records.best_sprint(lines).cloned()),
+            _ => None,
         };
-        let music = music::MusicInfo::new(ctx).expect("Failed to load music fileds");
         PlayingState {
-            delta: 0.,
             music,
-            leveling,
-            field: PlayingField::new(ctx),
-            piece: NoPiece(0., 0.),
-            next_pieces,
-            hold_piece: None,
-            score: 0,
-            on_hold: None,
-            destroying_rows_indices: None,
-            garbage_to_add: 0,
-            game_over: false,
+            engine: Engine::new(LevelingType::new(online, config.mode), config),
+            repeater: InputRepeater::new(settings.handling.clone()),
+            renderer,
+            events: vec![],
+            callout: None,
             online,
+            replay_status: None,
+            keys: 0,
+            personal_best,
+            new_best: false,
+            leaderboard: vec![],
+            place: None,
+            puzzle: None,
+            editor: None,
         }
     }
-    pub fn update(&mut self, ctx: &mut Context) -> GameResult<Option<GameState>> {
-        if self.game_over {
-            return Ok(None);
-        }
-        self.music.update();
-        // self.music.theme.play();
 
-        let dt = delta(ctx).as_secs_f64();
-        self.delta += dt;
+    pub fn puzzle(ctx: &mut Context, puzzle: &Puzzle, settings: &Settings) -> PlayingState {
+        PlayingState {
+            puzzle: Some(puzzle.id.clone()),
+            ..Self::with_config(ctx, false, puzzle.config(), settings)
+        }
+    }
 
-        if let LevelingType::Online(ref mut online) = self.leveling {
-            online.update(dt);
+    pub fn with_editor(self, editor: EditorState) -> PlayingState {
+        PlayingState {
+            editor: Some(Box::new(editor)),
+            ..self
         }
+    }
 
-        match self.piece {
-            Piece(ref piece) => {
-                let mut piece = piece.clone();
-                for _ in 0..(self.delta / self.leveling.get_gravity()).floor() as usize {
-                    if !self.check_hold(&piece, dt) {
-                        self.delta = 0.;
-                        piece = if let Some(p) = self.step_piece(piece, Dir::Down, true) {
-                            p
-                        } else {
-                            break;
-                        }
-                    } else {
-                        // println!("holding");
-                    }
-                }
-            }
-            NoPiece(ref mut time_elapsed, ref time_to_wait) => {
-                *time_elapsed += dt;
+    /// Where to go when leaving the game: the editor it was started from, or the menu
+    pub fn exit_state(&self) -> GameState {
+        match &self.editor {
+            Some(editor) => GameState::Editor(*editor.clone()),
+            None => GameState::default(),
+        }
+    }
 
-                if *time_elapsed > *time_to_wait {
-                    self.add_new_piece(ctx)?;
-                    self.delta = 0.;
-                }
-            }
+    pub fn update(&mut self, ctx: &mut Context) -> GameResult<Option<GameState>> {
+        if self.engine.is_game_over() {
+            return Ok(None);
         }
+        self.music.update();
 
-        // else {
-        //     println!("spawn piece");
-        //     self.piece = Some(self.next_pieces.get(ctx));
-        // }
-        // else {
-        // }
-        // }
-        if let Some((mut ddelta, indices)) = self.destroying_rows_indices.clone() {
-            ddelta += dt;
-
-            if ddelta > DESTROY_DELTA {
-                self.drop_blocks(indices);
-                self.destroying_rows_indices = None;
-            } else {
-                if ddelta >= DESTROY_HIDE_DELTA && ddelta - dt < DESTROY_HIDE_DELTA {
-                    self.animate_destroying_invis(&indices);
-                }
-                self.destroying_rows_indices = Some((ddelta, indices));
+        let dt = delta(ctx).as_secs_f64();
+        if let Some((ref mut time_left, _)) = self.callout {
+            *time_left -= dt;
+            if *time_left <= 0. {
+                self.callout = None;
             }
         }
-        if self.garbage_to_add > 0 {
-            self.add_one_garbage_line();
-            self.garbage_to_add -= 1;
-        }
+        self.engine.update(dt);
+        self.repeater.update(dt, &mut self.engine);
+        self.collect_events();
         Ok(None)
     }
 
-    pub fn update_net(
-        &mut self,
-        delta: f64,
-        prev_state: PlayingState,
-        net: &mut Netinfo,
-    ) -> Result<(), String> {
+    pub fn update_net(&mut self, delta: f64, net: &mut Netinfo) -> Result<(), String> {
         net.delta(delta);
         if net.last_sent >= net::HEARTBEAT_INTERVAL {
-            net.heartbeat();
+            net.heartbeat()?;
         }
         if net.last_response >= net::TIMEOUT {
             return Err("Connection timed out".to_string());
         }
-        if self.game_over && !prev_state.game_over {
-            net.game_over();
-        }
-        if let Some(indices) = &self.destroying_rows_indices {
-            if prev_state.destroying_rows_indices.is_none() {
-                net.lines(indices.1.len());
+        for event in std::mem::replace(&mut self.events, vec![]) {
+            match event {
+                EngineEvent::GameOver | EngineEvent::Finished => net.game_over()?,
+                EngineEvent::Attack(lines) => net.lines(lines)?,
+                EngineEvent::LinesCleared(_) => {}
+                EngineEvent::PieceLocked => net.height(self.engine.field().stack_height())?,
             }
         }
-        if self.field.height() != prev_state.field.height() {
-            net.height(self.field.height());
-        }
-        Ok(())
+        net.board(MirrorBoard::from_engine(&self.engine))
     }
 
-    pub fn pressed(
-        &mut self,
-        ctx: &mut Context,
-        key: KeyCode,
-        net: Option<&Netinfo>,
-    ) -> Option<GameState> {
-        use KeyCode::*;
+    pub fn pressed(&mut self, action: Action, net: Option<&Netinfo>) -> Option<GameState> {
+        use Action::*;
         let mut ret = None;
-        if self.game_over {
-            match key {
-                Space | Return => self.reset(ctx),
-                Escape => ret = Some(GameState::default()),
+        if self.engine.is_game_over() {
+            match action {
+                HardDrop | RotateCW | RotateCCW => self.reset(),
+                Pause => ret = Some(self.exit_state()),
                 _ => {}
             }
-        } else if let Piece(piece) = self.piece.clone() {
-            match key {
-                Up => {
-                    self.step_piece(piece, Dir::Up, false);
-                }
-                Down => {
-                    self.step_piece(piece, Dir::Down, false);
-                }
-                Left => {
-                    self.step_piece(piece, Dir::Left, false);
-                }
-                Right => {
-                    self.step_piece(piece, Dir::Right, false);
-                }
-                Space | Return => {
-                    //  else {
-                    self.rotate_piece(RDir::CClockwise);
-                    // }
-                }
-                Tab => {
-                    self.rotate_piece(RDir::Clockwise);
-                }
-                Q | J => {
-                    self.swap_hold(ctx).expect("Failed to swap hold");
-                }
-                P | Escape => {
+        } else {
+            let input = match action {
+                Pause => {
+                    self.repeater.release_all();
+                    self.music.pause_menu();
                     if let Some(net) = net {
                         ret = Some(GameState::PausedOnline(
                             PlayingOnlineState::Connected(self.clone()),
@@ -200,361 +159,629 @@ impl PlayingState {
                     } else {
                         ret = Some(GameState::Paused(self.clone(), PausedMenuState::default()));
                     }
+                    None
                 }
-                _ => {}
+                _ => action.input(),
+            };
+            if let Some(input) = input {
+                if self.repeater.press(input, &mut self.engine) {
+                    self.keys += 1;
+                }
+                self.collect_events();
             }
         }
-        self.check_hold_pressed();
         ret
     }
 
+    /// The state to continue with after the pause menu
+    pub fn resumed(&self) -> PlayingState {
+        self.music.unpause();
+        self.clone()
+    }
+
+    pub fn released(&mut self, action: Action) {
+        if let Some(input) = action.input() {
+            self.repeater.release(input);
+        }
+    }
+
+    /// Draws the garbage meters of both sides and the opponent's board
+    pub fn draw_opponent(&mut self, ctx: &mut Context, enemy: &EnemyState) -> GameResult<()> {
+        self.renderer
+            .draw_garbage_meter(ctx, self.engine.pending_garbage())?;
+        self.renderer.draw_mirror(ctx, &enemy.board)
+    }
+
     pub fn draw(&mut self, ctx: &mut Context, font: Font) -> GameResult<()> {
         clear(ctx, colors::BG.into());
-        self.field.draw(ctx)?;
+        self.renderer.draw_field(ctx, self.engine.field())?;
         sidebar::draw(
             ctx,
-            self.hold_piece.as_ref(),
-            self.next_pieces.as_vec(),
+            &mut self.renderer,
+            self.engine.hold_piece(),
+            self.engine.next_pieces(),
             font,
         )?;
-
-        if !self.game_over {
-            if let LevelingType::Single(single) = &self.leveling {
-                let lvl = single.get_level();
-                let score = single.get_score();
-                BetterText::new(&format!("Level {}", lvl.as_usize()), font)
-                    .align(Align::TL(8., 8.))
-                    .color(colors::LGREY)
-                    .draw(ctx)?;
-
-                BetterText::new(&format!("{}", score), font)
-                    .align(Align::TR(8., 8.))
-                    .color(colors::LGREY)
-                    .draw(ctx)?;
-            }
-            let field_offset =
-                FIELD_ALIGN.to_offset(get_win_dim(ctx), (FIELD_WIDTH_REAL, FIELD_HEIGHT_VIS_REAL));
-            let field_inner_align = Align::TL(field_offset.0, field_offset.1);
-            if let Piece(piece) = self.piece.clone() {
-                {
-                    let mut ghost_piece = piece.clone();
-                    while !self.field.colliding(&ghost_piece) {
-                        ghost_piece.step(Dir::Down);
-                    }
-                    ghost_piece.step(Dir::Up);
-                    ghost_piece.draw(ctx, field_inner_align, false, PieceAppearance::Ghost)?;
+        sidebar::draw_stats(ctx, font, &self.stats())?;
+
+        if !self.engine.is_game_over() {
+            match self.engine.leveling() {
+                LevelingType::Single(single) => {
+                    let lvl = single.get_level();
+                    let score = single.get_score();
+                    BetterText::new(&format!("Level {}", lvl), font)
+                        .align(Align::TL(8., 8.))
+                        .color(colors::LGREY)
+                        .draw(ctx)?;
+                    let lines = match single.goal() {
+                        Some(goal) => format!("{}/{} lines", single.get_lines(), goal),
+                        None => format!("{} lines", single.get_lines()),
+                    };
+                    BetterText::new(&lines, font)
+                        .align(Align::TL(8., 30.))
+                        .size(14)
+                        .color(colors::GREY)
+                        .draw(ctx)?;
+
+                    BetterText::new(&format!("{}", score), font)
+                        .align(Align::TR(8., 8.))
+                        .color(colors::LGREY)
+                        .draw(ctx)?;
+                }
+                LevelingType::Sprint(sprint) => self.draw_sprint(ctx, font, sprint)?,
+                LevelingType::Ultra(ultra) => {
+                    let left = ultra.duration() as f64 - self.engine.time();
+                    BetterText::new(&format_time(left), font)
+                        .align(Align::TL(8., 8.))
+                        .color(colors::LGREY)
+                        .draw(ctx)?;
+                    BetterText::new(&format!("{}", ultra.scoring.get_score()), font)
+                        .align(Align::TR(8., 8.))
+                        .color(colors::LGREY)
+                        .draw(ctx)?;
+                }
+                LevelingType::Dig(dig) => {
+                    BetterText::new(&format_time(self.engine.time()), font)
+                        .align(Align::TL(8., 8.))
+                        .color(colors::LGREY)
+                        .draw(ctx)?;
+                    BetterText::new(&format!("{} left", dig.remaining()), font)
+                        .align(Align::TR(8., 8.))
+                        .color(colors::LGREY)
+                        .draw(ctx)?;
+                }
+                LevelingType::Puzzle(puzzle) => {
+                    BetterText::new(&puzzle.goal().name(), font)
+                        .align(Align::TL(8., 8.))
+                        .size(14)
+                        .color(colors::LGREY)
+                        .draw(ctx)?;
+                    let left = self.engine.pieces_left().unwrap_or(0);
+                    BetterText::new(&format!("{} pieces left", left), font)
+                        .align(Align::TR(8., 8.))
+                        .size(14)
+                        .color(colors::LGREY)
+                        .draw(ctx)?;
+                }
+                LevelingType::Master(master) => {
+                    let level = format!("Level {}/{}", master.level(), master.section_end());
+                    BetterText::new(&level, font)
+                        .align(Align::TL(8., 8.))
+                        .color(colors::LGREY)
+                        .draw(ctx)?;
+                    BetterText::new(&format_time(self.engine.time()), font)
+                        .align(Align::TL(8., 30.))
+                        .size(14)
+                        .color(colors::GREY)
+                        .draw(ctx)?;
+                    BetterText::new(&format!("Grade {}", master.grade()), font)
+                        .align(Align::TR(8., 8.))
+                        .color(colors::LGREY)
+                        .draw(ctx)?;
+                    BetterText::new(&format!("{}", master.score()), font)
+                        .align(Align::TR(8., 30.))
+                        .size(14)
+                        .color(colors::GREY)
+                        .draw(ctx)?;
                 }
-                piece.draw(ctx, field_inner_align, false, PieceAppearance::Normal)?;
+                LevelingType::Online(_) => {}
             }
-        } else {
-            let (win_w, win_h) = crate::get_win_dim(ctx);
-            Mesh::new_rectangle(
-                ctx,
-                DrawMode::fill(),
-                Rect::new(0., 0., win_w, win_h),
-                colors::BG_ERR_OVERLAY.into(),
-            )?
-            .draw(ctx, DrawParam::default())?;
-            BetterText::new("GAME OVER", font)
-                .color(colors::LRED)
-                .size(23)
-                .align(Align::CC(0., -50.))
-                .draw(ctx)?;
-
-            if let LevelingType::Single(single) = &self.leveling {
-                let score = single.get_score();
-                BetterText::new(&format!("score:{}", score), font)
-                    .align(Align::BC(0., 30.))
-                    .color(colors::LGREY)
+            if let Some((_, text)) = &self.callout {
+                BetterText::new(text, font)
+                    .align(Align::TC(0., 60.))
+                    .size(14)
+                    .color(colors::YELLOW)
                     .draw(ctx)?;
             }
+            self.renderer.draw_falling_piece(ctx, &self.engine)?;
+        } else {
+            match self.engine.leveling() {
+                LevelingType::Single(single) if self.engine.is_finished() => {
+                    self.draw_marathon_results(ctx, font, single)?
+                }
+                LevelingType::Sprint(sprint) if self.engine.is_finished() => {
+                    self.draw_sprint_results(ctx, font, sprint)?
+                }
+                LevelingType::Ultra(ultra) if self.engine.is_finished() => {
+                    self.draw_ultra_results(ctx, font, ultra)?
+                }
+                LevelingType::Dig(dig) if self.engine.is_finished() => {
+                    self.draw_dig_results(ctx, font, dig)?
+                }
+                LevelingType::Puzzle(puzzle) if self.engine.is_finished() => {
+                    self.draw_puzzle_results(ctx, font, puzzle)?
+                }
+                LevelingType::Master(master) => self.draw_master_results(ctx, font, master)?,
+                _ => self.draw_game_over(ctx, font)?,
+            }
         }
 
         Ok(())
     }
 
-    pub fn add_garbage_lines(&mut self, amount: usize) {
-        self.garbage_to_add += amount;
+    fn draw_game_over(&self, ctx: &mut Context, font: Font) -> GameResult<()> {
+        let (win_w, win_h) = get_win_dim(ctx);
+        Mesh::new_rectangle(
+            ctx,
+            DrawMode::fill(),
+            Rect::new(0., 0., win_w, win_h),
+            colors::BG_ERR_OVERLAY.into(),
+        )?
+        .draw(ctx, DrawParam::default())?;
+        BetterText::new("GAME OVER", font)
+            .color(colors::LRED)
+            .size(23)
+            .align(Align::CC(0., -50.))
+            .draw(ctx)?;
+
+        if let LevelingType::Single(single) = self.engine.leveling() {
+            let score = single.get_score();
+            BetterText::new(&format!("score:{}", score), font)
+                .align(Align::BC(0., 30.))
+                .color(colors::LGREY)
+                .draw(ctx)?;
+        }
+        self.draw_footer(ctx, font)
     }
 
-    fn add_new_piece(&mut self, ctx: &mut Context) -> GameResult {
-        let mut piece = self.next_pieces.get(ctx)?;
-        piece.step(Dir::Down);
-        match self.field.colliding_reason(&piece) {
-            CollidingReason::None | CollidingReason::TopOut => {}
-            _ => {
-                piece.step(Dir::Up);
-                self.game_over = match self.field.colliding_reason(&piece) {
-                    CollidingReason::None | CollidingReason::TopOut => false,
-                    _ => true,
-                };
-            }
+    /// Seed and replay status at the bottom of the end screens
+    fn draw_footer(&self, ctx: &mut Context, font: Font) -> GameResult<()> {
+        BetterText::new(&format!("seed:{}", self.engine.seed()), font)
+            .align(Align::BC(0., 8.))
+            .size(10)
+            .color(colors::GREY)
+            .draw(ctx)?;
+        if let Some(status) = &self.replay_status {
+            BetterText::new(status, font)
+                .align(Align::BC(0., 60.))
+                .size(10)
+                .color(colors::GREY)
+                .draw(ctx)?;
         }
-        // } else {
-        self.piece = Piece(piece);
-        // }
         Ok(())
     }
 
-    fn add_one_garbage_line(&mut self) {
-        let block = Block::new(BlockColor::Garbage);
-        let mut row = [Some(block); FIELD_WIDTH];
-        row[thread_rng().gen_range(0, FIELD_WIDTH)] = None;
-        self.field.add_row(self.field.height() - 1, row);
+    /// Timer, lines left and the last split compared to the personal best
+    fn draw_sprint(&self, ctx: &mut Context, font: Font, sprint: &Sprint) -> GameResult<()> {
+        BetterText::new(&format_time(self.engine.time()), font)
+            .align(Align::TL(8., 8.))
+            .color(colors::LGREY)
+            .draw(ctx)?;
+        BetterText::new(&format!("{}/{}", sprint.lines(), sprint.goal()), font)
+            .align(Align::TR(8., 8.))
+            .color(colors::LGREY)
+            .draw(ctx)?;
+
+        let i = match sprint.splits().len().checked_sub(1) {
+            Some(i) => i,
+            None => return Ok(()),
+        };
+        let split = sprint.splits()[i];
+        let mut text = format!("{}L {}", (i + 1) * SPRINT_SPLIT_LINES, format_time(split));
+        let mut color = colors::LGREY;
+        if let Some(best) = self.personal_best.as_ref().and_then(|pb| pb.splits.get(i)) {
+            text += &format!(" {}", format_diff(split - best));
+            color = if split <= *best {
+                colors::GREEN
+            } else {
+                colors::LRED
+            };
+        }
+        BetterText::new(&text, font)
+            .align(Align::TL(8., 34.))
+            .size(12)
+            .color(color)
+            .draw(ctx)
     }
 
-    /*fn remove_destroying(&mut self, indices: &Vec<usize>) {
-        for index in indices {
-            self.field.remove_row(*index);
-        }
-    }*/
-
-    fn swap_hold(&mut self, ctx: &mut Context) -> GameResult {
-        use HoldPiece::*;
-        if let Piece(mut old_piece) = self.piece.clone() {
-            if let Some(Unlocked(mut new_piece)) = self.hold_piece.clone() {
-                new_piece.set_scaling(1.);
-                self.piece = Piece(new_piece);
-                // Reset piece state
-                old_piece.reset();
-                old_piece.set_scaling(HOLD_PIECE_SCALING);
-                self.hold_piece = Some(Locked(old_piece));
-            } else if self.hold_piece.is_none() {
-                self.add_new_piece(ctx)?;
-                old_piece.set_scaling(HOLD_PIECE_SCALING);
-                self.hold_piece = Some(Locked(old_piece));
+    fn draw_sprint_results(&self, ctx: &mut Context, font: Font, sprint: &Sprint) -> GameResult {
+        let (win_w, win_h) = get_win_dim(ctx);
+        Mesh::new_rectangle(
+            ctx,
+            DrawMode::fill(),
+            Rect::new(0., 0., win_w, win_h),
+            colors::DARK_OVERLAY.into(),
+        )?
+        .draw(ctx, DrawParam::default())?;
+        BetterText::new("FINISHED", font)
+            .color(colors::WHITE)
+            .size(23)
+            .align(Align::CC(0., -90.))
+            .draw(ctx)?;
+        BetterText::new(&format_time(self.engine.time()), font)
+            .color(colors::YELLOW)
+            .size(20)
+            .align(Align::CC(0., -55.))
+            .draw(ctx)?;
+
+        let best = if self.new_best {
+            "new personal best!".to_string()
+        } else if let Some(pb) = &self.personal_best {
+            format!("personal best: {}", format_time(pb.time))
+        } else {
+            String::new()
+        };
+        BetterText::new(&best, font)
+            .color(colors::LGREY)
+            .size(12)
+            .align(Align::CC(0., -30.))
+            .draw(ctx)?;
+
+        let mut y = -5.;
+        for (i, split) in sprint.splits().iter().enumerate() {
+            let mut text = format!("{}L {}", (i + 1) * SPRINT_SPLIT_LINES, format_time(*split));
+            if let Some(best) = self.personal_best.as_ref().and_then(|pb| pb.splits.get(i)) {
+                text += &format!(" {}", format_diff(split - best));
             }
+            BetterText::new(&text, font)
+                .color(colors::LGREY)
+                .size(11)
+                .align(Align::CC(0., y))
+                .draw(ctx)?;
+            y += 16.;
         }
-        Ok(())
+        self.draw_footer(ctx, font)
     }
 
-    fn animate_destroying_invis(&mut self, indices: &[usize]) {
-        let row = [None; FIELD_WIDTH];
-        for index in indices {
-            self.field.set_row(*index, row);
+    fn draw_ultra_results(&self, ctx: &mut Context, font: Font, ultra: &Ultra) -> GameResult {
+        let (win_w, win_h) = get_win_dim(ctx);
+        Mesh::new_rectangle(
+            ctx,
+            DrawMode::fill(),
+            Rect::new(0., 0., win_w, win_h),
+            colors::DARK_OVERLAY.into(),
+        )?
+        .draw(ctx, DrawParam::default())?;
+        BetterText::new("TIME UP", font)
+            .color(colors::WHITE)
+            .size(23)
+            .align(Align::CC(0., -150.))
+            .draw(ctx)?;
+        let scoring = &ultra.scoring;
+        BetterText::new(&format!("{}", scoring.get_score()), font)
+            .color(colors::YELLOW)
+            .size(20)
+            .align(Align::CC(0., -115.))
+            .draw(ctx)?;
+        let summary = format!(
+            "lines {}  pieces {}  pps {:.2}",
+            scoring.get_lines(),
+            self.engine.pieces(),
+            self.engine.pieces() as f64 / ultra.duration() as f64
+        );
+        BetterText::new(&summary, font)
+            .color(colors::LGREY)
+            .size(12)
+            .align(Align::CC(0., -90.))
+            .draw(ctx)?;
+
+        let mut y = -60.;
+        for (i, record) in self.leaderboard.iter().enumerate() {
+            let color = if self.place == Some(i) {
+                colors::YELLOW
+            } else {
+                colors::LGREY
+            };
+            let text = format!(
+                "{:>2}. {:>7}  {:>3} lines",
+                i + 1,
+                record.score,
+                record.lines
+            );
+            BetterText::new(&text, font)
+                .color(color)
+                .size(11)
+                .align(Align::CC(0., y))
+                .draw(ctx)?;
+            y += 16.;
         }
+        self.draw_footer(ctx, font)
     }
 
-    fn drop_blocks(&mut self, indices_unsorted: Vec<usize>) {
-        let mut field_cpy = self.field.to_vec_shallow();
-        let empty_row = [None; FIELD_WIDTH];
-        // let mut rows_removed = 0;
-        let mut indices = indices_unsorted;
-        indices.sort();
-        for index in indices {
-            field_cpy.remove(index); //- rows_removed
-            field_cpy.insert(0, empty_row.clone());
-            // rows_removed += 1;
-        }
-        // for _ in 0..rows_removed {}
-        for (y, row) in field_cpy.iter().enumerate() {
-            self.field.set_row(y, *row);
-        }
+    fn draw_marathon_results(
+        &self,
+        ctx: &mut Context,
+        font: Font,
+        single: &Singleplayer,
+    ) -> GameResult {
+        let (win_w, win_h) = get_win_dim(ctx);
+        Mesh::new_rectangle(
+            ctx,
+            DrawMode::fill(),
+            Rect::new(0., 0., win_w, win_h),
+            colors::DARK_OVERLAY.into(),
+        )?
+        .draw(ctx, DrawParam::default())?;
+        BetterText::new("MARATHON COMPLETE", font)
+            .color(colors::WHITE)
+            .size(23)
+            .align(Align::CC(0., -90.))
+            .draw(ctx)?;
+        BetterText::new(&format!("{}", single.get_score()), font)
+            .color(colors::YELLOW)
+            .size(20)
+            .align(Align::CC(0., -55.))
+            .draw(ctx)?;
+        let summary = format!(
+            "{} lines  {}  pieces {}",
+            single.get_lines(),
+            format_time(self.engine.game_time()),
+            self.engine.pieces()
+        );
+        BetterText::new(&summary, font)
+            .color(colors::LGREY)
+            .size(12)
+            .align(Align::CC(0., -30.))
+            .draw(ctx)?;
+        self.draw_footer(ctx, font)
     }
 
-    /// Returns the modified piece if it still exists
-    fn step_piece(&mut self, piece: Piece, dir: Dir, lock: bool) -> Option<Piece> {
-        // let mut piece = piece.clone();
-        let mut piece = piece;
-        let before_piece = piece.clone();
-        // let mut mod_piece  = piece.clone();
-        // if self.field.colliding(&before_piece) {
-        //     println!("called step piece on piece that is already colliding!");
-        // }
-
-        // let maybe_mod_piece =
-        match dir {
-            Dir::Left | Dir::Right => {
-                piece.step(dir);
-                if !self.field.colliding(&piece) {
-                    self.piece = Piece(piece.clone());
-                    Some(piece)
-                } else {
-                    None
-                }
-            }
-            Dir::Down => {
-                piece.step(dir);
-                // println!("stepped down. Offset now: {:?}", piece.offset);
-                if !self.field.colliding(&piece) {
-                    self.piece = Piece(piece.clone());
-                    Some(piece)
-                } else if lock {
-                    self.add_piece(before_piece.clone());
-                    // Some(before_piece)
-                    None
-                } else {
-                    None
-                }
-            }
-            Dir::Up => {
-                while !self.field.colliding(&piece) {
-                    piece.step(Dir::Down);
-                    // println!("stepping fast");
-                }
-                piece.step(Dir::Up);
-                self.add_piece(piece.clone());
-                None
-            }
+    /// Shown whether the game was finished or not, since the grade counts either way
+    fn draw_master_results(&self, ctx: &mut Context, font: Font, master: &Master) -> GameResult {
+        let (win_w, win_h) = get_win_dim(ctx);
+        let (title, overlay) = if self.engine.is_finished() {
+            ("MASTER CLEARED", colors::DARK_OVERLAY)
+        } else {
+            ("GAME OVER", colors::BG_ERR_OVERLAY)
+        };
+        Mesh::new_rectangle(
+            ctx,
+            DrawMode::fill(),
+            Rect::new(0., 0., win_w, win_h),
+            overlay.into(),
+        )?
+        .draw(ctx, DrawParam::default())?;
+        BetterText::new(title, font)
+            .color(colors::WHITE)
+            .size(23)
+            .align(Align::CC(0., -110.))
+            .draw(ctx)?;
+        BetterText::new(&format!("Grade {}", master.grade()), font)
+            .color(colors::YELLOW)
+            .size(20)
+            .align(Align::CC(0., -75.))
+            .draw(ctx)?;
+        let summary = format!(
+            "level {}  score {}  {}",
+            master.level(),
+            master.score(),
+            format_time(self.engine.game_time())
+        );
+        BetterText::new(&summary, font)
+            .color(colors::LGREY)
+            .size(12)
+            .align(Align::CC(0., -50.))
+            .draw(ctx)?;
+        let mut last = 0.;
+        for (i, time) in master.sections().iter().enumerate() {
+            let line = format!(
+                "{:>3}  {}  {}",
+                ((i + 1) * MASTER_SECTION_LEVELS).min(MASTER_LEVEL_MAX),
+                format_time(*time),
+                format_time(time - last)
+            );
+            BetterText::new(&line, font)
+                .color(colors::GREY)
+                .size(12)
+                .align(Align::CC(0., -25. + i as f32 * 16.))
+                .draw(ctx)?;
+            last = *time;
         }
+        self.draw_footer(ctx, font)
     }
 
-    fn rotate_piece(&mut self, rdir: RDir) {
-        // println!("rotating");
-        if let Piece(mut piece) = self.piece.clone() {
-            let start_rot = piece.get_rotation();
-            let end_rot = piece.get_rotation() + rdir;
-            piece.set_rotation(end_rot.clone());
-
-            for step_dir_pairs in piece.rot_steps(start_rot, end_rot).iter() {
-                let mut moved_piece = piece.clone();
-                for step_dir in step_dir_pairs {
-                    if let Some(dir) = step_dir {
-                        moved_piece.step(*dir);
-                    }
-                }
-                // println!("trying...");
-                if !self.field.colliding(&moved_piece) {
-                    self.piece = Piece(moved_piece);
-                    // println!("Rot worked with offset {:?}", step_dir_pairs);
-                    return;
-                }
-            }
-            // println!("didnt work");
-        }
+    fn draw_dig_results(&self, ctx: &mut Context, font: Font, dig: &Dig) -> GameResult {
+        let (win_w, win_h) = get_win_dim(ctx);
+        Mesh::new_rectangle(
+            ctx,
+            DrawMode::fill(),
+            Rect::new(0., 0., win_w, win_h),
+            colors::DARK_OVERLAY.into(),
+        )?
+        .draw(ctx, DrawParam::default())?;
+        BetterText::new("CLEARED", font)
+            .color(colors::WHITE)
+            .size(23)
+            .align(Align::CC(0., -90.))
+            .draw(ctx)?;
+        BetterText::new(&format_time(self.engine.game_time()), font)
+            .color(colors::YELLOW)
+            .size(20)
+            .align(Align::CC(0., -55.))
+            .draw(ctx)?;
+        let pieces = self.engine.pieces();
+        let summary = format!(
+            "{} garbage rows  pieces {}  {:.2} pieces/row",
+            dig.rows(),
+            pieces,
+            pieces as f64 / dig.rows() as f64
+        );
+        BetterText::new(&summary, font)
+            .color(colors::LGREY)
+            .size(12)
+            .align(Align::CC(0., -30.))
+            .draw(ctx)?;
+        self.draw_footer(ctx, font)
     }
 
-    fn reset(&mut self, ctx: &mut Context) {
-        *self = PlayingState::new(ctx, self.online);
+    fn draw_puzzle_results(
+        &self,
+        ctx: &mut Context,
+        font: Font,
+        puzzle: &PuzzleProgress,
+    ) -> GameResult {
+        let (win_w, win_h) = get_win_dim(ctx);
+        Mesh::new_rectangle(
+            ctx,
+            DrawMode::fill(),
+            Rect::new(0., 0., win_w, win_h),
+            colors::DARK_OVERLAY.into(),
+        )?
+        .draw(ctx, DrawParam::default())?;
+        BetterText::new("SOLVED", font)
+            .color(colors::WHITE)
+            .size(23)
+            .align(Align::CC(0., -90.))
+            .draw(ctx)?;
+        let summary = format!(
+            "{}  pieces {}  lines {}",
+            puzzle.goal().name(),
+            self.engine.pieces(),
+            puzzle.lines()
+        );
+        BetterText::new(&summary, font)
+            .color(colors::LGREY)
+            .size(12)
+            .align(Align::CC(0., -55.))
+            .draw(ctx)?;
+        self.draw_footer(ctx, font)
     }
 
-    fn add_piece(&mut self, piece: Piece) {
-        use HoldPiece::*;
-        let mut should_game_over = false;
-        if let Some(Locked(piece)) = &self.hold_piece {
-            self.hold_piece = Some(Unlocked(piece.clone()));
-        }
-
-        if self.field.add_piece(piece) {
-            // if overlapping {
-            //     println!("game over because of overlapping");
-            //     self.game_over = true;
-            // } else {
-            //
-            should_game_over = true;
-            // }
-        }
-
-        let destroyed_rows_indices = self.check_rows_destroying();
-        let time_to_wait = if destroyed_rows_indices.is_empty() {
-            ADD_PIECE_DELAY
+    /// Pieces per second and keys per piece
+    fn stats(&self) -> Vec<String> {
+        let pieces = self.engine.pieces();
+        let time = self.engine.time();
+        let pps = if time > 0. { pieces as f64 / time } else { 0. };
+        let kpp = if pieces > 0 {
+            self.keys as f64 / pieces as f64
         } else {
-            ADD_PIECE_DELAY_DESTROYED
+            0.
         };
-        self.piece = PieceState::NoPiece(0., time_to_wait);
-        if should_game_over && destroyed_rows_indices.is_empty() {
-            // top out with no rows about to be destroyed / marked destroying
-            self.game_over = true;
-        }
+        vec![format!("PPS {:.2}", pps), format!("KPP {:.2}", kpp)]
     }
 
-    /// Call once when a key is pressed
-    fn check_hold_pressed(&mut self) {
-        if let Piece(mut piece) = self.piece.clone() {
-            if !self.field.colliding(&piece) {
-                piece.step(Dir::Down);
-                if self.field.colliding(&piece) {
-                    if let Some(ref mut ohd) = self.on_hold {
-                        ohd.pressed();
-                    } else {
-                        self.on_hold = Some(OnHoldState::new());
-                    }
-                }
-            }
-        }
+    /// Writes the game so far to a new replay file
+    pub fn save_replay(&self) -> Result<(), String> {
+        let replay = Replay::new(&self.engine, self.online, self.repeater.handling().clone());
+        replay.save().map(|_| ())
     }
 
-    /// returns whether game should hold the piece
-    fn check_hold(&mut self, piece: &Piece, delta: f64) -> bool {
-        // if let Piece(mut piece) = self.piece.clone() {
-        let mut piece = piece.clone();
-        piece.step(Dir::Down);
-        // let would_collide = match self.field.colliding_reason(&piece) {
-        //     CollidingReason::None => false,
-        //     CollidingReason::HitFloor => return false,
-        //     _ => true,
-        // };
-        let would_collide = self.field.colliding(&piece);
-
-        if let Some(ref mut ohds) = self.on_hold {
-            if would_collide {
-                if ohds.hold_exceeded(delta) {
-                    // println!(
-                    //     "exceeded hold. moves: {}, last_time: {}, total_time: {}",
-                    //     ohds.moves_made,
-                    //     ohds.last_time_passed + delta,
-                    //     ohds.total_time_passed
-                    // );
-                    println!("Hold exceeded");
-                    self.on_hold = None;
-                    return false;
-                } else {
-                    //increased hold
-                    // self.on_hold = Some(ohds.update(delta));
-                    ohds.update(delta);
-                    // println!("Holding (state: )");
-                    return true;
+    pub fn add_garbage_lines(&mut self, amount: usize) {
+        self.engine.add_garbage_lines(amount);
+    }
+
+    /// Shows callouts for the engine's events and queues them to be sent
+    fn collect_events(&mut self) {
+        let events = self.engine.take_events();
+        for event in events.iter() {
+            match event {
+                EngineEvent::LinesCleared(clear) => {
+                    if let Some(text) = clear.callout() {
+                        self.callout = Some((CALLOUT_DURATION, text));
+                    }
                 }
-            } else {
-                self.on_hold = None;
-                return false;
+                EngineEvent::GameOver => self.save_replay_status(),
+                EngineEvent::Finished => {
+                    self.submit_record();
+                    self.save_replay_status();
+                }
+                _ => {}
             }
-        // } else {
-        //     // self.on_hold = Some(0.);
-        //     return false;
-        // }
-
-        // self.on_hold = Some(0.);
-        // return true;
-        // } else {
-        // not holding, no action. Hold gets initiated within keypress
-        // self.on_hold = None;
-        // return false;
-        } else if would_collide {
-            // println!("holding automatically");
-            self.on_hold = Some(OnHoldState::new());
-            return true;
         }
-        false
-        // } else {
-        // println!("no piece, no hold");
-        // }
-        // return false;
+        if self.online {
+            self.events.extend(events);
+        }
     }
 
-    /// returns Vec <index of rows that need to be destroyed>
-    fn check_rows_destroying(&mut self) -> Vec<usize> {
-        let mut row_indices_to_destroy: Vec<usize> = vec![];
-        let field_cpy = self.field.clone();
-        let row_destroying = [Some(Block::new(BlockColor::Destroying)); FIELD_WIDTH];
+    fn save_replay_status(&mut self) {
+        self.replay_status = Some(match self.save_replay() {
+            Ok(()) => "replay saved".to_string(),
+            Err(err) => format!("could not save replay: {}", err),
+        });
+    }
 
-        for (y, row) in field_cpy.to_vec().iter().enumerate() {
-            if !row.contains(&None) {
-                row_indices_to_destroy.push(y);
-                self.field.set_row(y, row_destroying.clone());
+    /// Saves the finished game if it is a new personal best or makes the leaderboard
+    fn submit_record(&mut self) {
+        let mut records = match Records::load() {
+            Ok(records) => records,
+            Err(err) => {
+                // saving would replace every other record
+                println!("{}\nThe new record is not saved", err);
+                return;
+            }
+        };
+        let changed = match self.engine.leveling() {
+            LevelingType::Sprint(sprint) => {
+                self.new_best = records.submit_sprint(SprintRecord {
+                    lines: sprint.goal(),
+                    time: self.engine.time(),
+                    splits: sprint.splits().to_vec(),
+                    pieces: self.engine.pieces(),
+                });
+                self.new_best
+            }
+            LevelingType::Puzzle(_) => match &self.puzzle {
+                Some(id) => records.solve(id),
+                None => false,
+            },
+            LevelingType::Ultra(ultra) => {
+                self.place = records.submit_ultra(UltraRecord {
+                    duration: ultra.duration(),
+                    score: ultra.scoring.get_score(),
+                    lines: ultra.scoring.get_lines(),
+                    pieces: self.engine.pieces(),
+                });
+                self.leaderboard = records
+                    .ultra_leaderboard(ultra.duration())
+                    .into_iter()
+                    .cloned()
+                    .collect();
+                self.place.is_some()
+            }
+            _ => false,
+        };
+        if changed {
+            if let Err(err) = records.save() {
+                println!("Could not save records:\n{}", err);
             }
         }
-
-        if !row_indices_to_destroy.is_empty() {
-            self.destroyed_lines(row_indices_to_destroy.len() as usize);
-            self.destroying_rows_indices = Some((0., row_indices_to_destroy.clone()));
-        }
-        row_indices_to_destroy
     }
 
-    fn destroyed_lines(&mut self, lines: usize) {
-        if let LevelingType::Single(ref mut single) = self.leveling {
-            single.cleared_lines(lines);
+    /// Starts a new game with the same settings and a new seed
+    fn reset(&mut self) {
+        let config = self.engine.config().reseeded();
+        self.engine = Engine::new(LevelingType::new(self.online, config.mode), config);
+        self.repeater = InputRepeater::new(self.repeater.handling().clone());
+        self.events.clear();
+        self.callout = None;
+        self.replay_status = None;
+        self.keys = 0;
+        self.leaderboard.clear();
+        self.place = None;
+        if self.new_best {
+            self.personal_best = match self.engine.config().mode {
+                GameMode::Sprint(lines) => Records::load()
+                    .ok()
+                    .and_then(|records| records.best_sprint(lines).cloned()),
+                _ => None,
+            };
+            self.new_best = false;
         }
     }
 }
+
+/// Formats a difference in seconds with its sign, like `-1.234`
+fn format_diff(secs: f64) -> String {
+    let sign = if secs <= 0. { '-' } else { '+' };
+    format!("{}{:.3}", sign, secs.abs())
+}
@@ -1,5 +1,5 @@
-use crate::block::BLOCK_SIZE;
-use crate::piece::{hold_piece::HOLD_PIECE_SCALING, HoldPiece, Piece, PieceAppearance};
+use crate::piece::{HoldPiece, Piece};
+use crate::render::{PieceAppearance, Renderer, HOLD_PIECE_SCALING};
 use crate::util::{colors, Align, BetterText};
 use ggez::{
     graphics::{DrawMode, DrawParam, Drawable, Font, Mesh, Rect},
@@ -7,12 +7,15 @@ use ggez::{
 };
 
 pub const SIDEBAR_MARGIN: f32 = 8.;
-pub const SIDEBAR_WIDTH: f32 =
-    SIDEBAR_MARGIN + BLOCK_SIZE * 4. * SIDEBAR_BLOCK_SCALING + SIDEBAR_MARGIN;
 pub const SIDEBAR_BLOCK_SCALING: f32 = 0.5;
 
+pub fn sidebar_width(block_size: f32) -> f32 {
+    SIDEBAR_MARGIN + block_size * 4. * SIDEBAR_BLOCK_SCALING + SIDEBAR_MARGIN
+}
+
 pub fn draw(
     ctx: &mut Context,
+    renderer: &mut Renderer,
     maybe_hold_piece: Option<&HoldPiece>,
     next_pieces: Vec<Piece>,
     font: Font,
@@ -20,7 +23,7 @@ pub fn draw(
     if let Some(hold_piece) = maybe_hold_piece {
         let (x_off, y_off) = (10., 50.);
         let hldp_border_margin = 6.;
-        let size = 3. * HOLD_PIECE_SCALING * BLOCK_SIZE + hldp_border_margin * 2.;
+        let size = 3. * HOLD_PIECE_SCALING * renderer.block_size() + hldp_border_margin * 2.;
         // let off = Align::TL(20., 50.).to_offset();
         Mesh::new_rectangle(
             ctx,
@@ -34,20 +37,37 @@ pub fn draw(
             .color(colors::GREY)
             .size(13)
             .draw(ctx)?;
-        hold_piece.draw(
+        renderer.draw_hold_piece(
             ctx,
+            hold_piece,
             Align::TL(x_off + hldp_border_margin, y_off + hldp_border_margin),
         )?;
     }
 
-    let mut next_pieces = next_pieces;
     let mut y = 50.;
-    for piece in next_pieces[0..6].iter_mut() {
-        piece.set_scaling(SIDEBAR_BLOCK_SCALING);
-
-        piece.draw(ctx, Align::TR(10., y), true, PieceAppearance::Normal)?;
+    for piece in next_pieces.iter().take(6) {
+        renderer.draw_piece(
+            ctx,
+            piece,
+            SIDEBAR_BLOCK_SCALING,
+            Align::TR(10., y),
+            true,
+            PieceAppearance::Normal,
+        )?;
         y += 40.;
     }
 
     Ok(())
 }
+
+/// Draws a few lines of statistics in the bottom left corner
+pub fn draw_stats(ctx: &mut Context, font: Font, lines: &[String]) -> GameResult {
+    for (i, line) in lines.iter().rev().enumerate() {
+        BetterText::new(line, font)
+            .align(Align::BL(10., 10. + i as f32 * 18.))
+            .color(colors::GREY)
+            .size(11)
+            .draw(ctx)?;
+    }
+    Ok(())
+}
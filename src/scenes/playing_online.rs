@@ -1,10 +1,11 @@
+use crate::controls::Action;
 use crate::game::GameState;
-use crate::net::Netinfo;
+use crate::net::{MirrorBoard, Netinfo};
 use crate::scenes::playing::PlayingState;
 use crate::util::colors;
 use crate::util::{Align, BetterText};
 use ggez::graphics::{clear, Font};
-use ggez::{event::KeyCode, timer::delta, Context, GameResult};
+use ggez::{timer::delta, Context, GameResult};
 
 #[derive(Clone, Debug)]
 pub enum PlayingOnlineState {
@@ -16,7 +17,7 @@ impl PlayingOnlineState {
     pub fn pressed(
         &mut self,
         ctx: &mut Context,
-        key: KeyCode,
+        action: Action,
         net: &mut Netinfo,
     ) -> Option<GameState> {
         use PlayingOnlineState::*;
@@ -26,10 +27,9 @@ impl PlayingOnlineState {
 
         match self {
             Connected(ref mut playing_state) => {
-                let prev_state = playing_state.clone();
-                let ret = playing_state.pressed(ctx, key, Some(net));
+                let ret = playing_state.pressed(action, Some(net));
 
-                if let Err(msg) = playing_state.update_net(dt, prev_state, net) {
+                if let Err(msg) = playing_state.update_net(dt, net) {
                     Some(GameState::PlayingOnline(
                         PlayingOnlineState::Disconnected(msg),
                         net.clone(),
@@ -38,13 +38,13 @@ impl PlayingOnlineState {
                     ret
                 }
             }
-            Disconnected(_) => match key {
-                KeyCode::Escape => Some(GameState::default()),
+            Disconnected(_) => match action {
+                Action::Pause => Some(GameState::default()),
                 _ => None,
             },
         }
     }
-    pub fn draw(&mut self, ctx: &mut Context, font: Font) -> GameResult {
+    pub fn draw(&mut self, ctx: &mut Context, font: Font, enemy: &EnemyState) -> GameResult {
         use PlayingOnlineState::*;
         match self {
             Disconnected(msg) => {
@@ -60,6 +60,7 @@ impl PlayingOnlineState {
             }
             Connected(ps) => {
                 ps.draw(ctx, font)?;
+                ps.draw_opponent(ctx, enemy)?;
             }
         }
 
@@ -78,6 +79,8 @@ impl PlayingOnlineState {
 pub struct EnemyState {
     pub height: usize,
     pub game_over: bool,
+    /// The opponent's board as far as it was streamed
+    pub board: MirrorBoard,
 }
 
 impl EnemyState {
@@ -85,6 +88,7 @@ impl EnemyState {
         EnemyState {
             height: 0,
             game_over: false,
+            board: MirrorBoard::new(),
         }
     }
 }
@@ -0,0 +1,146 @@
+use crate::engine::{Engine, Replay, TICK_DELTA};
+use crate::game::GameState;
+use crate::render::Renderer;
+use crate::scenes::menu::{replays::ReplayListState, MenuScreen};
+use crate::scenes::playing::sidebar;
+use crate::settings::Settings;
+use crate::util::{colors, format_time, Align, BetterText};
+
+use ggez::{event::KeyCode, graphics::*, timer::delta, Context, GameResult};
+
+const SEEK_TICKS: u64 = 5 * 60;
+const MIN_SPEED: f64 = 0.25;
+const MAX_SPEED: f64 = 4.;
+
+/// Plays a recorded game back by feeding its inputs into a fresh engine
+#[derive(Clone, Debug)]
+pub struct ReplayState {
+    replay: Replay,
+    engine: Engine,
+    renderer: Renderer,
+    next_event: usize,
+    delta: f64,
+    speed: f64,
+    paused: bool,
+}
+
+impl ReplayState {
+    pub fn new(ctx: &mut Context, replay: Replay, settings: &Settings) -> ReplayState {
+        let renderer = Renderer::new(ctx, settings.block_size).expect("Failed to build meshes");
+        ReplayState {
+            engine: replay.engine(),
+            replay,
+            renderer,
+            next_event: 0,
+            delta: 0.,
+            speed: 1.,
+            paused: false,
+        }
+    }
+
+    pub fn update(&mut self, ctx: &mut Context) {
+        if self.paused {
+            return;
+        }
+        self.delta += delta(ctx).as_secs_f64() * self.speed;
+        while self.delta >= TICK_DELTA {
+            self.delta -= TICK_DELTA;
+            if !self.step() {
+                self.paused = true;
+                self.delta = 0.;
+                break;
+            }
+        }
+    }
+
+    /// Applies the inputs due before the next tick and runs it.
+    /// Returns false once the end of the replay is reached.
+    fn step(&mut self) -> bool {
+        while let Some((tick, event)) = self.replay.events.get(self.next_event).cloned() {
+            if tick > self.engine.ticks() {
+                break;
+            }
+            Replay::apply(&mut self.engine, event);
+            self.next_event += 1;
+        }
+        if self.engine.ticks() >= self.replay.ticks || self.engine.is_game_over() {
+            return false;
+        }
+        self.engine.tick();
+        self.engine.take_events();
+        true
+    }
+
+    /// Jumps to a tick, replaying from the start if it lies in the past
+    fn seek(&mut self, tick: u64) {
+        if tick < self.engine.ticks() {
+            self.engine = self.replay.engine();
+            self.next_event = 0;
+        }
+        while self.engine.ticks() < tick && self.step() {}
+        self.delta = 0.;
+    }
+
+    pub fn pressed(&mut self, key: KeyCode) -> Option<GameState> {
+        use KeyCode::*;
+        let ticks = self.engine.ticks();
+        match key {
+            Space | Return => self.paused = !self.paused,
+            Left => self.seek(ticks.saturating_sub(SEEK_TICKS)),
+            Right => self.seek(ticks + SEEK_TICKS),
+            Home => self.seek(0),
+            Up => self.speed = (self.speed * 2.).min(MAX_SPEED),
+            Down => self.speed = (self.speed / 2.).max(MIN_SPEED),
+            Period => {
+                self.paused = true;
+                self.step();
+            }
+            Escape => return Some(GameState::Menu(MenuScreen::Replays(ReplayListState::new()))),
+            _ => {}
+        }
+        None
+    }
+
+    pub fn draw(&mut self, ctx: &mut Context, font: Font) -> GameResult<()> {
+        clear(ctx, colors::BG.into());
+        self.renderer.draw_field(ctx, self.engine.field())?;
+        sidebar::draw(
+            ctx,
+            &mut self.renderer,
+            self.engine.hold_piece(),
+            self.engine.next_pieces(),
+            font,
+        )?;
+        self.renderer.draw_falling_piece(ctx, &self.engine)?;
+
+        let time = format!(
+            "{} / {}",
+            format_time(self.engine.game_time()),
+            format_time(self.replay.ticks as f64 * TICK_DELTA)
+        );
+        BetterText::new(&time, font)
+            .align(Align::TL(8., 8.))
+            .size(12)
+            .color(colors::LGREY)
+            .draw(ctx)?;
+        let status = if self.paused {
+            "paused".to_string()
+        } else {
+            format!("x{}", self.speed)
+        };
+        BetterText::new(&status, font)
+            .align(Align::TR(8., 8.))
+            .size(12)
+            .color(colors::LGREY)
+            .draw(ctx)?;
+        BetterText::new(
+            "Space: pause  Left/Right: seek  Up/Down: speed  .: step",
+            font,
+        )
+        .align(Align::BC(0., 8.))
+        .size(10)
+        .color(colors::GREY)
+        .draw(ctx)?;
+        Ok(())
+    }
+}
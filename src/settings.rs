@@ -0,0 +1,115 @@
+use crate::controls::Controls;
+use crate::engine::Handling;
+use crate::field::BoardSize;
+use crate::render::field_off;
+use crate::scenes::playing::sidebar::sidebar_width;
+use crate::util::store::{self, Dir};
+use ggez::conf::WindowMode;
+use serde::{Deserialize, Serialize};
+
+const SETTINGS_FILE: &str = "settings.toml";
+
+pub const MIN_BLOCK_SIZE: f32 = 15.;
+pub const MAX_BLOCK_SIZE: f32 = 50.;
+const BOTTOM_MARGIN: f32 = 40.;
+
+/// Everything the player can configure. Saved as TOML in the user's config directory.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default = "Settings::default")]
+pub struct Settings {
+    /// Music volume from 0 to 1
+    pub volume: f32,
+    /// Part of the volume that is left while the game is paused
+    pub paused_volume: f32,
+    pub window_width: f32,
+    pub window_height: f32,
+    /// Side length of a block on screen, in pixels
+    pub block_size: f32,
+    pub handling: Handling,
+    pub controls: Controls,
+}
+
+impl Settings {
+    pub fn default() -> Settings {
+        let block_size = 25.;
+        let (window_width, window_height) = min_window_size(block_size);
+        Settings {
+            volume: 1.,
+            paused_volume: 0.3,
+            window_width,
+            window_height,
+            block_size,
+            handling: Handling::default(),
+            controls: Controls::default(),
+        }
+    }
+
+    /// Loads the saved settings, the defaults if there are none yet
+    pub fn load() -> Result<Settings, String> {
+        let settings = store::load(Dir::Config, SETTINGS_FILE, Settings::default)?;
+        let validated = settings.clone().validated();
+        if validated != settings {
+            println!("Some settings were out of range and have been adjusted");
+        }
+        Ok(validated)
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        store::save(Dir::Config, SETTINGS_FILE, self)
+    }
+
+    /// Clamps every value into its valid range
+    pub fn validated(mut self) -> Settings {
+        self.volume = clamp(self.volume, 0., 1.);
+        self.paused_volume = clamp(self.paused_volume, 0., 1.);
+        self.block_size = clamp(self.block_size, MIN_BLOCK_SIZE, MAX_BLOCK_SIZE).round();
+        let (min_width, min_height) = min_window_size(self.block_size);
+        self.window_width = self.window_width.max(min_width);
+        self.window_height = self.window_height.max(min_height);
+
+        let handling = &mut self.handling;
+        handling.das = clamp(handling.das, 0., 1.);
+        handling.arr = clamp(handling.arr, 0., 0.5);
+        handling.dcd = clamp(handling.dcd, 0., 0.5);
+        handling.soft_drop_factor = handling.soft_drop_factor.map(|f| clamp(f, 1., 100.));
+
+        self.controls = self.controls.validated();
+        self
+    }
+
+    pub fn window_mode(&self) -> WindowMode {
+        let (min_width, min_height) = min_window_size(self.block_size);
+        WindowMode {
+            min_width,
+            min_height,
+            width: self.window_width,
+            height: self.window_height,
+            resizable: true,
+            ..Default::default()
+        }
+    }
+}
+
+/// The smallest window that fits the usual field and the sidebar at the given block size
+pub fn min_window_size(block_size: f32) -> (f32, f32) {
+    window_size_for(block_size, BoardSize::default())
+}
+
+/// The smallest window that fits a field of this size and the sidebar
+pub fn window_size_for(block_size: f32, size: BoardSize) -> (f32, f32) {
+    let field_off = field_off(block_size);
+    (
+        size.width as f32 * block_size + field_off.0 + sidebar_width(block_size),
+        (size.height + 1) as f32 * block_size + field_off.1 + BOTTOM_MARGIN,
+    )
+}
+
+fn clamp<T: PartialOrd>(value: T, min: T, max: T) -> T {
+    if value < min {
+        min
+    } else if value > max {
+        max
+    } else {
+        value
+    }
+}
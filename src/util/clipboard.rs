@@ -0,0 +1,27 @@
+use clipboard::{ClipboardContext, ClipboardProvider};
+use std::cell::RefCell;
+
+thread_local! {
+    // kept alive because on X11 the copied text is gone once its owner is dropped
+    static CLIPBOARD: RefCell<Option<ClipboardContext>> = RefCell::new(None);
+}
+
+fn with_clipboard<T>(
+    f: impl FnOnce(&mut ClipboardContext) -> Result<T, Box<dyn std::error::Error>>,
+) -> Result<T, String> {
+    CLIPBOARD.with(|clipboard| {
+        let mut clipboard = clipboard.borrow_mut();
+        if clipboard.is_none() {
+            *clipboard = Some(ClipboardProvider::new().map_err(|e| e.to_string())?);
+        }
+        f(clipboard.as_mut().unwrap()).map_err(|e| e.to_string())
+    })
+}
+
+pub fn copy(text: String) -> Result<(), String> {
+    with_clipboard(|clipboard| clipboard.set_contents(text))
+}
+
+pub fn paste() -> Result<String, String> {
+    with_clipboard(|clipboard| clipboard.get_contents())
+}
@@ -0,0 +1,176 @@
+use serde::{Deserialize, Serialize};
+
+/// Moves that restart the lock delay with move reset
+const LOCK_MOVES_MAX: usize = 15;
+
+/// What restarts the lock delay of a piece on the ground
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum LockDown {
+    /// Every move or rotation
+    Infinite,
+    /// Every move or rotation, up to `LOCK_MOVES_MAX` of them
+    MoveReset,
+    /// Only falling to a lower row
+    StepReset,
+}
+
+impl LockDown {
+    pub fn all() -> Vec<LockDown> {
+        vec![LockDown::MoveReset, LockDown::Infinite, LockDown::StepReset]
+    }
+
+    pub fn name(self) -> String {
+        match self {
+            LockDown::Infinite => "Infinite".to_string(),
+            LockDown::MoveReset => "Move Reset".to_string(),
+            LockDown::StepReset => "Step Reset".to_string(),
+        }
+    }
+}
+
+/// Decides when the falling piece locks. Lives as long as the piece.
+///
+/// The delay only runs while the piece is on the ground and is restarted by the moves
+/// `LockDown` allows. Reaching a row lower than any before always restarts it and gives
+/// back the moves used, so a piece can't be kept up by moving it up and down.
+#[derive(Clone, Debug)]
+pub struct LockDelay {
+    lock_down: LockDown,
+    /// Time a piece can rest on the ground before it locks
+    delay: f64,
+    /// Time spent on the ground since the delay was last restarted
+    time_on_ground: f64,
+    /// Moves that restarted the delay since the piece reached `lowest_row`
+    resets: usize,
+    /// Lowest row the piece has been on, larger is lower
+    lowest_row: isize,
+    on_ground: bool,
+}
+
+impl LockDelay {
+    pub fn new(lock_down: LockDown, delay: f64, row: isize, on_ground: bool) -> LockDelay {
+        LockDelay {
+            lock_down,
+            delay,
+            time_on_ground: 0.,
+            resets: 0,
+            lowest_row: row,
+            on_ground,
+        }
+    }
+
+    /// Call after every successful move, rotation or fall of the piece
+    pub fn moved(&mut self, row: isize, on_ground: bool) {
+        self.on_ground = on_ground;
+        if row > self.lowest_row {
+            self.lowest_row = row;
+            self.resets = 0;
+            self.time_on_ground = 0.;
+            return;
+        }
+        match self.lock_down {
+            LockDown::Infinite => self.time_on_ground = 0.,
+            LockDown::MoveReset if self.resets < LOCK_MOVES_MAX => {
+                self.resets += 1;
+                self.time_on_ground = 0.;
+            }
+            LockDown::MoveReset | LockDown::StepReset => {}
+        }
+    }
+
+    pub fn update(&mut self, delta: f64) {
+        if self.on_ground {
+            self.time_on_ground += delta;
+        }
+    }
+
+    /// Whether the piece has to lock where it is now
+    pub fn should_lock(&self) -> bool {
+        let out_of_moves = self.lock_down == LockDown::MoveReset && self.resets >= LOCK_MOVES_MAX;
+        self.on_ground && (self.time_on_ground >= self.delay || out_of_moves)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DELAY: f64 = 0.5;
+    const ROW: isize = 10;
+
+    fn on_ground(lock_down: LockDown) -> LockDelay {
+        LockDelay::new(lock_down, DELAY, ROW, true)
+    }
+
+    #[test]
+    fn lower_row_restores_moves_and_time() {
+        let mut lock = on_ground(LockDown::MoveReset);
+        for _ in 0..LOCK_MOVES_MAX - 1 {
+            lock.moved(ROW, true);
+        }
+        lock.update(DELAY - 0.1);
+        lock.moved(ROW + 1, true);
+        lock.update(DELAY - 0.1);
+        assert!(!lock.should_lock());
+        for _ in 0..LOCK_MOVES_MAX - 1 {
+            lock.moved(ROW + 1, true);
+        }
+        assert!(!lock.should_lock());
+    }
+
+    #[test]
+    fn last_move_reset_on_ground_locks() {
+        let mut lock = on_ground(LockDown::MoveReset);
+        for _ in 0..LOCK_MOVES_MAX - 1 {
+            lock.moved(ROW, true);
+        }
+        assert!(!lock.should_lock());
+        lock.moved(ROW, true);
+        assert!(lock.should_lock());
+    }
+
+    #[test]
+    fn out_of_moves_in_the_air_waits_for_the_ground() {
+        let mut lock = on_ground(LockDown::MoveReset);
+        for _ in 0..LOCK_MOVES_MAX {
+            lock.moved(ROW, false);
+        }
+        assert!(!lock.should_lock());
+        lock.moved(ROW, true);
+        assert!(lock.should_lock());
+    }
+
+    #[test]
+    fn step_reset_ignores_moves_and_rotations() {
+        let mut lock = on_ground(LockDown::StepReset);
+        lock.update(DELAY - 0.1);
+        lock.moved(ROW, true);
+        lock.moved(ROW, true);
+        lock.update(0.1);
+        assert!(lock.should_lock());
+    }
+
+    #[test]
+    fn infinite_never_runs_out_of_moves() {
+        let mut lock = on_ground(LockDown::Infinite);
+        for _ in 0..LOCK_MOVES_MAX * 10 {
+            lock.update(DELAY - 0.1);
+            lock.moved(ROW, true);
+            assert!(!lock.should_lock());
+        }
+        lock.update(DELAY);
+        assert!(lock.should_lock());
+    }
+
+    #[test]
+    fn time_only_runs_on_the_ground() {
+        let mut lock = LockDelay::new(LockDown::MoveReset, DELAY, ROW, false);
+        lock.update(DELAY * 4.);
+        assert!(!lock.should_lock());
+        lock.moved(ROW, true);
+        lock.update(DELAY - 0.1);
+        assert!(!lock.should_lock());
+        lock.update(0.1);
+        assert!(lock.should_lock());
+    }
+}
@@ -1,12 +1,18 @@
 pub mod alignment;
 pub mod bettertext;
+pub mod clipboard;
 pub mod colors;
+mod lock_delay;
 pub mod music;
-mod on_hold;
 mod rng_piece_queue;
+pub mod store;
+mod text_input;
+mod time;
 pub mod types;
 
 pub use alignment::Alignment as Align;
 pub use bettertext::BetterText;
-pub use on_hold::OnHoldState;
+pub use lock_delay::{LockDelay, LockDown};
 pub use rng_piece_queue::RngPieceQueue;
+pub use text_input::TextInput;
+pub use time::format_time;
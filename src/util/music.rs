@@ -10,17 +10,17 @@ use std::rc::Rc;
 
 type ASource = Rc<RefCell<Source>>;
 
-const PAUSED_VOL: f32 = 0.3;
-
 #[derive(Clone, Debug)]
 pub struct MusicInfo {
     current_source: ASource,
     sources: MusicSources,
     playing: bool,
+    volume: f32,
+    paused_volume: f32, // part of the volume left while paused
 }
 
 impl MusicInfo {
-    pub fn new(ctx: &mut Context) -> GameResult<MusicInfo> {
+    pub fn new(ctx: &mut Context, volume: f32, paused_volume: f32) -> GameResult<MusicInfo> {
         let f = |ctx: &mut Context, s| -> GameResult<ASource> {
             Ok(Rc::new(RefCell::new(Source::new(ctx, s)?)))
         };
@@ -28,10 +28,13 @@ impl MusicInfo {
             theme_a: f(ctx, "/audio/music/theme_a.ogg")?,
             theme_b: f(ctx, "/audio/music/theme_a.ogg")?,
         };
+        sources.theme_a.try_borrow_mut().unwrap().set_volume(volume);
         Ok(MusicInfo {
             current_source: Rc::clone(&sources.theme_a),
             sources,
             playing: false,
+            volume,
+            paused_volume,
         })
     }
     pub fn update(&mut self) {
@@ -46,10 +49,13 @@ impl MusicInfo {
         self.current_source
             .try_borrow_mut()
             .unwrap()
-            .set_volume(PAUSED_VOL);
+            .set_volume(self.volume * self.paused_volume);
     }
     pub fn unpause(&self) {
-        self.current_source.try_borrow_mut().unwrap().set_volume(1.);
+        self.current_source
+            .try_borrow_mut()
+            .unwrap()
+            .set_volume(self.volume);
     }
 }
 
@@ -1,40 +1,43 @@
-use crate::{
-    piece::{Piece, PieceShape},
-    scenes::playing::sidebar::SIDEBAR_BLOCK_SCALING,
-};
-use ggez::{Context, GameResult};
-use rand::seq::SliceRandom;
+use crate::engine::PieceGenerator;
+use crate::piece::Piece;
 
 #[derive(Clone, Debug)]
 pub struct RngPieceQueue {
+    generator: Box<dyn PieceGenerator>,
     pieces: Vec<Piece>,
+    taken: usize,
 }
 
 impl RngPieceQueue {
-    pub fn new(ctx: &mut Context) -> GameResult<Self> {
-        let mut rbq = Self { pieces: vec![] };
-        rbq.fill(ctx)?;
-        Ok(rbq)
+    pub fn new(generator: Box<dyn PieceGenerator>) -> Self {
+        let mut rbq = Self {
+            generator,
+            pieces: vec![],
+            taken: 0,
+        };
+        rbq.fill();
+        rbq
     }
-    pub fn get(&mut self, ctx: &mut Context) -> GameResult<Piece> {
-        let mut piece = self.pieces.remove(0);
-        self.fill(ctx)?;
-        piece.set_scaling(1.);
-        Ok(piece)
+    pub fn get(&mut self) -> Piece {
+        let piece = self.pieces.remove(0);
+        self.taken += 1;
+        self.fill();
+        piece
     }
 
-    pub fn fill(&mut self, ctx: &mut Context) -> GameResult {
-        let mut rng = rand::thread_rng();
+    pub fn fill(&mut self) {
         while self.pieces.len() <= 7 {
-            let mut random_pieces = PieceShape::all();
-            random_pieces.shuffle(&mut rng);
-            for form in random_pieces {
-                self.pieces
-                    .push(Piece::new(ctx, form, SIDEBAR_BLOCK_SCALING)?);
-            }
+            self.pieces.push(Piece::new(self.generator.next_shape()));
         }
+    }
+
+    /// Pieces taken out of the queue so far
+    pub fn taken(&self) -> usize {
+        self.taken
+    }
 
-        Ok(())
+    pub fn seed(&self) -> u64 {
+        self.generator.seed()
     }
 
     /// Returns a clone of the Vec<Piece>
@@ -0,0 +1,47 @@
+use serde::{de::DeserializeOwned, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+/// Where a file of the game is kept, each in a `tetro-98` directory of the user
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Dir {
+    /// For what the player configures
+    Config,
+    /// For what the game remembers
+    Data,
+}
+
+/// Loads a TOML file, `default` if there is none yet.
+/// A file that can't be read is an error, so the caller can tell before it gets replaced.
+pub fn load<T: DeserializeOwned>(
+    dir: Dir,
+    file: &str,
+    default: impl Fn() -> T,
+) -> Result<T, String> {
+    let path = path(dir, file)?;
+    if !path.exists() {
+        return Ok(default());
+    }
+    fs::read_to_string(&path)
+        .map_err(|e| e.to_string())
+        .and_then(|text| toml::from_str(&text).map_err(|e| e.to_string()))
+        .map_err(|e| format!("Could not load {}: {}", path.display(), e))
+}
+
+pub fn save<T: Serialize>(dir: Dir, file: &str, value: &T) -> Result<(), String> {
+    let path = path(dir, file)?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    let text = toml::to_string(value).map_err(|e| e.to_string())?;
+    fs::write(&path, text).map_err(|e| e.to_string())
+}
+
+fn path(dir: Dir, file: &str) -> Result<PathBuf, String> {
+    let base = match dir {
+        Dir::Config => dirs::config_dir().ok_or("No config directory found"),
+        Dir::Data => dirs::data_dir().ok_or("No data directory found"),
+    };
+    base.map(|base| base.join("tetro-98").join(file))
+        .map_err(|e| e.to_string())
+}
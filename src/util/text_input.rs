@@ -0,0 +1,126 @@
+use super::clipboard;
+use ggez::event::KeyCode;
+
+/// A line of text the player types into, with a caret.
+/// Characters come from ggez's `text_input_event`, editing keys from key presses.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextInput {
+    text: Vec<char>,
+    /// Index of the character the caret stands before
+    caret: usize,
+    max_len: usize,
+}
+
+impl TextInput {
+    pub fn new(text: &str, max_len: usize) -> TextInput {
+        let mut input = TextInput {
+            text: vec![],
+            caret: 0,
+            max_len,
+        };
+        input.set_text(text);
+        input
+    }
+
+    pub fn text(&self) -> String {
+        self.text.iter().collect()
+    }
+
+    /// Replaces the text and puts the caret at its end
+    pub fn set_text(&mut self, text: &str) {
+        self.text = text.chars().take(self.max_len).collect();
+        self.caret = self.text.len();
+    }
+
+    /// Inserts a typed character at the caret. Control characters and
+    /// characters beyond the maximum length are dropped.
+    pub fn typed(&mut self, c: char) {
+        if c.is_control() || self.text.len() >= self.max_len {
+            return;
+        }
+        self.text.insert(self.caret, c);
+        self.caret += 1;
+    }
+
+    /// Inserts the first line of the clipboard at the caret
+    pub fn paste(&mut self) -> Result<(), String> {
+        let pasted = clipboard::paste()?;
+        for c in pasted.lines().next().unwrap_or("").trim().chars() {
+            self.typed(c);
+        }
+        Ok(())
+    }
+
+    /// Handles the editing keys. Returns whether the key was one of them.
+    pub fn pressed(&mut self, key: KeyCode) -> bool {
+        use KeyCode::*;
+        match key {
+            Back if self.caret > 0 => {
+                self.caret -= 1;
+                self.text.remove(self.caret);
+            }
+            Delete if self.caret < self.text.len() => {
+                self.text.remove(self.caret);
+            }
+            Left => self.caret = self.caret.saturating_sub(1),
+            Right => self.caret = (self.caret + 1).min(self.text.len()),
+            Home => self.caret = 0,
+            End => self.caret = self.text.len(),
+            Back | Delete => {}
+            _ => return false,
+        }
+        true
+    }
+
+    /// Returns the text with a bar where the caret is
+    pub fn with_caret(&self) -> String {
+        let mut text = self.text.clone();
+        text.insert(self.caret, '|');
+        text.into_iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn typing_inserts_at_the_caret() {
+        let mut input = TextInput::new("ac", 4);
+        input.pressed(KeyCode::Left);
+        input.typed('b');
+        assert_eq!(input.with_caret(), "ab|c");
+        input.typed('\n');
+        input.typed('d');
+        input.typed('e');
+        assert_eq!(input.with_caret(), "abd|c");
+    }
+
+    #[test]
+    fn backspace_and_delete() {
+        let mut input = TextInput::new("abc", 10);
+        input.pressed(KeyCode::Left);
+        assert!(input.pressed(KeyCode::Back));
+        assert_eq!(input.with_caret(), "a|c");
+        assert!(input.pressed(KeyCode::Delete));
+        assert_eq!(input.with_caret(), "a|");
+        input.pressed(KeyCode::Home);
+        assert!(input.pressed(KeyCode::Back));
+        assert_eq!(input.with_caret(), "|a");
+    }
+
+    #[test]
+    fn caret_stays_in_the_text() {
+        let mut input = TextInput::new("ab", 10);
+        input.pressed(KeyCode::Right);
+        assert_eq!(input.with_caret(), "ab|");
+        input.pressed(KeyCode::Home);
+        input.pressed(KeyCode::Left);
+        assert_eq!(input.with_caret(), "|ab");
+        input.pressed(KeyCode::Right);
+        assert_eq!(input.with_caret(), "a|b");
+        input.pressed(KeyCode::End);
+        assert_eq!(input.with_caret(), "ab|");
+        assert!(!input.pressed(KeyCode::Up));
+    }
+}
@@ -0,0 +1,10 @@
+/// Formats seconds as `m:ss.mmm`
+pub fn format_time(secs: f64) -> String {
+    let millis = (secs.max(0.) * 1000.).round() as u64;
+    format!(
+        "{}:{:02}.{:03}",
+        millis / 60_000,
+        millis / 1000 % 60,
+        millis % 1000
+    )
+}
@@ -1,6 +1,6 @@
 use ggez::event::KeyCode;
 use ggez::mint::Point2;
-use std::ops::Mul;
+use std::ops::{Add, Mul};
 
 #[derive(Clone, Debug, Copy)]
 pub enum Dir {
@@ -15,6 +15,7 @@ pub enum Dir {
 pub enum RDir {
     Clockwise,
     CClockwise,
+    Half,
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -61,6 +62,15 @@ impl GridPoint {
     // }
 }
 
+impl Add<(isize, isize)> for GridPoint {
+    type Output = GridPoint;
+    fn add(mut self, other: (isize, isize)) -> Self::Output {
+        self.x += other.0;
+        self.y += other.1;
+        self
+    }
+}
+
 impl Mul<isize> for GridPoint {
     type Output = GridPoint;
     fn mul(mut self, other: isize) -> Self::Output {